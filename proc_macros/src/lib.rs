@@ -5,7 +5,7 @@ use core::panic;
 
 use proc_macro::{TokenStream, Span};
 use quote::{quote, format_ident};
-use syn::{parse_macro_input, DeriveInput, Data, PathArguments, GenericArgument, Path, Type};
+use syn::{parse_macro_input, punctuated::Punctuated, DeriveInput, Data, Expr, PathArguments, GenericArgument, Path, Token, Type};
 
 
 /// This derive macro is intended to be used in combination with the 
@@ -80,10 +80,39 @@ pub fn derive_configuration_plugin(input: TokenStream) -> TokenStream {
 ///     }
 /// }
 /// ```
+///
+/// The macro also implements [`third_life::config::ConfigFields`] for the
+/// struct, which is how fields in the json files that don't exist on the
+/// struct are found and reported instead of silently ignored. Nested structs
+/// that also derive [`Config`] are checked as well, even inside of `Option`s
+/// and `Vec`s. Structs with a `#[serde(flatten)]` field only check their
+/// nested structs since the flattened one could take any field.
+///
+///
+/// Values can be constrained with attributes on the fields, both bounds are
+/// inclusive:
+/// - `#[range(0.0, 1.0)]` for a value between two bounds,
+/// - `#[min(1)]` for a value that has to be at least the bound.
+///
+/// A function that checks the struct as a whole, like whether some fields
+/// add up, is added with `#[validate(function)]` on the struct. It takes the
+/// struct by reference and returns a `Result<(), String>` with the problem.
+///
+/// The macro also describes the fields of the struct, with their defaults,
+/// doc comments and constraints, through
+/// [`third_life::config::shape::DescribeConfig`]. This is what the config
+/// editor and the json schemas are built from, so the defaults have to be
+/// serializable.
+///
+/// All of them are checked by the `validate` method of the generated
+/// [`third_life::config::ConfigConstraints`] implementation, which goes
+/// through the nested configs as well and reports every violation with its
+/// json path. Defaults are not checked.
+///
 /// `IMPORTANT` Using this macro creates a implementation for the struct meaning
 /// no other implementaion can be created. This is fine in my opinion since its
 /// only configuration but is still to be considered.
-#[proc_macro_derive(Config, attributes(def))]
+#[proc_macro_derive(Config, attributes(def, range, min, validate))]
 pub fn derive_getters(input: TokenStream) -> TokenStream {
     let ast = parse_macro_input!(input as DeriveInput);
     let expanded = expand_getters(ast);
@@ -121,6 +150,10 @@ fn expand_getters(mut ast: DeriveInput) -> TokenStream {
         _ => panic!("#[derive(Config)] can only be used on braced structs")
     };
 
+    let config_fields = expand_config_fields(&ast);
+    let config_constraints = expand_config_constraints(&ast);
+    let config_shape = expand_config_shape(&ast);
+
 
     let quotes: Vec<_> = fields.iter().map(|(field, ty, def)| {
         match (ty, def) {
@@ -170,9 +203,234 @@ fn expand_getters(mut ast: DeriveInput) -> TokenStream {
 
             #(#quotes)*
         }
+
+        #config_fields
+
+        #config_constraints
+
+        #config_shape
     }.into()
 }
 
+/// The bounds inside of a `#[range(..)]` or `#[min(..)]`.
+fn constraint_bounds(attr: &syn::Attribute) -> Vec<Expr> {
+    attr.parse_args_with(Punctuated::<Expr, Token![,]>::parse_terminated)
+        .unwrap_or_else(|_| panic!("#[{}(..)] takes the bounds separated by commas", 
+            attr.path.get_ident().unwrap()))
+        .into_iter()
+        .collect()
+}
+
+/// Text of the doc comments, lines are joined and empty lines start a new
+/// paragraph.
+fn doc_comment(attrs: &[syn::Attribute]) -> String {
+    let lines = attrs.iter()
+        .filter(|attr| attr.path.is_ident("doc"))
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(syn::Meta::NameValue(syn::MetaNameValue { lit: syn::Lit::Str(text), .. })) => {
+                Some(text.value().trim().to_string())
+            },
+            _ => None,
+        });
+    lines.fold(String::new(), |doc, line| match (doc.is_empty(), line.is_empty()) {
+        (true, _) => line,
+        (false, true) => doc + "\n\n",
+        (false, false) if doc.ends_with('\n') => doc + &line,
+        (false, false) => doc + " " + &line,
+    })
+}
+
+/// Implements [`third_life::config::shape::DescribeConfig`], a field with a
+/// default is described by the type inside of its `Option`.
+fn expand_config_shape(ast: &DeriveInput) -> proc_macro2::TokenStream {
+    let name = &ast.ident;
+    let Data::Struct(ref data) = ast.data else {
+        panic!("#[derive(Config)] can only be used on braced structs")
+    };
+    let fields = data.fields.iter().map(|f| {
+        let key = f.ident.as_ref().unwrap().to_string();
+        let description = doc_comment(&f.attrs);
+        let def = f.attrs.iter().find(|attr| attr.path.is_ident("def")).map(|attr| &attr.tokens);
+        let (ty, default) = match (extract_type_from_option(&f.ty), def) {
+            (Some(ty), Some(def)) => (ty, quote! {
+                serde_json::to_value({ let default: #ty = #def; default }).ok()
+            }),
+            _ => (&f.ty, quote! { None }),
+        };
+        let (mut minimum, mut maximum) = (quote! { None }, quote! { None });
+        for attr in &f.attrs {
+            let bounds = || constraint_bounds(attr).into_iter().map(|b| quote! { Some((#b) as f64) });
+            if attr.path.is_ident("range") {
+                let mut bounds = bounds();
+                minimum = bounds.next().unwrap();
+                maximum = bounds.next().unwrap();
+            } else if attr.path.is_ident("min") {
+                minimum = bounds().next().unwrap();
+            }
+        }
+        quote! {
+            crate::config::shape::FieldShape {
+                name: #key,
+                description: #description,
+                default: #default,
+                minimum: #minimum,
+                maximum: #maximum,
+                shape: (&ShapeOf::<#ty>::new()).shape(),
+            }
+        }
+    });
+
+    quote! {
+        impl crate::config::shape::DescribeConfig for #name {
+            fn shape() -> crate::config::shape::ConfigShape {
+                #[allow(unused_imports)]
+                use crate::config::shape::{ShapeOf, NestedShape, PlainShape};
+                crate::config::shape::ConfigShape::Struct(vec![#(#fields),*])
+            }
+        }
+    }
+}
+
+/// Implements [`third_life::config::ConfigConstraints`] out of the `range`,
+/// `min` and `validate` attributes, then hands every field to its own type
+/// in case it is a config with constraints as well.
+fn expand_config_constraints(ast: &DeriveInput) -> proc_macro2::TokenStream {
+    let name = &ast.ident;
+    let Data::Struct(ref data) = ast.data else {
+        panic!("#[derive(Config)] can only be used on braced structs")
+    };
+    let field_checks = data.fields.iter().flat_map(|f| {
+        let field = f.ident.as_ref().unwrap();
+        let key = field.to_string();
+        f.attrs.iter().filter_map(move |attr| {
+            let check = if attr.path.is_ident("range") {
+                let [low, high] = &constraint_bounds(attr)[..] else {
+                    panic!("#[range(..)] takes exactly two bounds, like #[range(0.0, 1.0)]")
+                };
+                quote! {
+                    if !(#low..=#high).contains(&value) {
+                        Some(format!("has to be between {} and {}, but is {}", #low, #high, value))
+                    } else {
+                        None
+                    }
+                }
+            } else if attr.path.is_ident("min") {
+                let [min] = &constraint_bounds(attr)[..] else {
+                    panic!("#[min(..)] takes exactly one bound, like #[min(1)]")
+                };
+                quote! {
+                    if value < #min {
+                        Some(format!("has to be at least {}, but is {}", #min, value))
+                    } else {
+                        None
+                    }
+                }
+            } else {
+                return None;
+            };
+            Some(quote! {
+                {
+                    let value = self.#field();
+                    if let Some(message) = #check {
+                        violations.push(crate::config::error::ConfigViolation {
+                            path: crate::config::field_path(path, #key),
+                            message,
+                        });
+                    }
+                }
+            })
+        })
+    }).collect::<Vec<_>>();
+
+    let struct_checks = ast.attrs.iter()
+        .filter(|attr| attr.path.is_ident("validate"))
+        .map(|attr| {
+            let function: Path = attr.parse_args()
+                .expect("#[validate(..)] takes the path of a function");
+            quote! {
+                if let Err(message) = #function(self) {
+                    violations.push(crate::config::error::ConfigViolation {
+                        path: path.to_string(),
+                        message,
+                    });
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let fields: Vec<_> = data.fields.iter().map(|f| f.ident.as_ref().unwrap()).collect();
+    let keys: Vec<_> = fields.iter().map(|f| f.to_string()).collect();
+
+    quote! {
+        impl crate::config::ConfigConstraints for #name {
+            fn violations(
+                &self,
+                path: &str,
+                violations: &mut Vec<crate::config::error::ConfigViolation>,
+            ) {
+                #[allow(unused_imports)]
+                use crate::config::{ConstraintsOf, NestedConstraints, PlainConstraints};
+                #(#field_checks)*
+                #(#struct_checks)*
+                #(
+                    (&ConstraintsOf(&self.#fields)).violations(
+                        &crate::config::field_path(path, #keys), violations
+                    );
+                )*
+            }
+        }
+    }
+}
+
+/// Implements [`third_life::config::ConfigFields`]. Every field of the json
+/// object that is not a field of the struct is unknown, then every known
+/// field is handed to its own type, which only does something if that type
+/// implements the trait as well.
+fn expand_config_fields(ast: &DeriveInput) -> proc_macro2::TokenStream {
+    let name = &ast.ident;
+    let Data::Struct(ref data) = ast.data else {
+        panic!("#[derive(Config)] can only be used on braced structs")
+    };
+    let flattened = data.fields.iter().any(|f| f.attrs.iter().any(|attr| {
+        attr.path.is_ident("serde") && attr.tokens.to_string().contains("flatten")
+    }));
+    let names: Vec<_> = data.fields.iter()
+        .map(|f| f.ident.as_ref().unwrap().to_string())
+        .collect();
+    let types: Vec<_> = data.fields.iter().map(|f| &f.ty).collect();
+    let check_keys = (!flattened).then(|| quote! {
+        for key in object.keys() {
+            if ![#(#names),*].contains(&key.as_str()) {
+                unknown.push(crate::config::field_path(path, key));
+            }
+        }
+    });
+
+    quote! {
+        impl crate::config::ConfigFields for #name {
+            fn unknown_fields(
+                value: &serde_json::Value,
+                path: &str,
+                unknown: &mut Vec<String>,
+            ) {
+                #[allow(unused_imports)]
+                use crate::config::{FieldsOf, NestedFields, PlainFields};
+                let Some(object) = value.as_object() else {
+                    return;
+                };
+                #check_keys
+                #(
+                    if let Some(value) = object.get(#names) {
+                        (&FieldsOf::<#types>::new()).unknown_fields(
+                            value, &crate::config::field_path(path, #names), unknown
+                        );
+                    }
+                )*
+            }
+        }
+    }
+}
+
 fn extract_type_from_option(ty: &Type) -> Option<&Type> {
     fn path_is_option(path: &Path) -> bool {
         path.leading_colon.is_none()
@@ -1,12 +1,55 @@
 use bevy::prelude::*;
 
-use crate::worlds::config::SpriteConfig;
+use crate::{
+    config::SelectedConfigPath,
+    worlds::{config::{SpriteConfig, WorldConfig}, init_colonies, WorldColony},
+    SimulationState,
+};
 
 pub struct ThirdLifeAnimationPlugin;
 
 impl Plugin for ThirdLifeAnimationPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, animate_sprites);
+        app
+            .add_systems(
+                OnEnter(SimulationState::Running),
+                init_colony_visuals.after(init_colonies)
+            )
+            .add_systems(Update, animate_sprites);
+    }
+}
+
+/// Gives every colony its animated sprite and a label with its name. Only
+/// needed when there is a window to render to.
+fn init_colony_visuals(
+    mut commands: Commands,
+    colonies: Query<(Entity, &WorldConfig), With<WorldColony>>,
+    asset_server: Res<AssetServer>,
+    config_path: Res<SelectedConfigPath>,
+    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+) {
+    let font = asset_server.load("fonts/VictorMonoNerdFontMono-Medium.ttf");
+    for (colony, world) in &colonies {
+        let texture = asset_server.load(format!(
+            "{}/sprite_sheets/{}",
+            config_path.0,
+            world.sprite().sprite_sheet()
+        ));
+        commands.entity(colony).insert(ColonyAnimationBundle::new(
+                world.name(), world.world_position(),
+                texture, &mut texture_atlas_layouts,
+                world.sprite()
+        )).with_children(|parent| {
+            parent.spawn(
+                Text2dBundle {
+                    text: Text::from_section(world.name(), TextStyle { 
+                        font: font.clone(), font_size: 24., color: Color::WHITE 
+                    }).with_justify(JustifyText::Center),
+                    transform: Transform::from_xyz(0., -(world.sprite().shape().0 as f32), 0.),
+                    ..default()
+                }
+            );
+        });
     }
 }
 
@@ -0,0 +1,404 @@
+//! Running many headless simulations at once to compare setups.
+//!
+//! Every combination of the given config folders and seeds is a single run,
+//! built with [`build_app`] so it behaves exactly like a run of the headless
+//! or windowed app with the same config and seed. The runs are spread over a
+//! number of worker threads and each one adds a [`RunSummaryPlugin`] which
+//! reports a [`RunSummary`] per world once the run ends.
+//!
+//! With a [`Sweep`] every config and seed combination is additionally run
+//! once per variant of the sweep, and the swept values become columns of the
+//! summary.
+//!
+//! The entry point is `bin/batch.rs`.
+
+pub mod sweep;
+
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use bevy::{app::AppExit, prelude::*};
+use chrono::NaiveDate;
+use clap::Parser;
+
+use crate::{
+    config::{error::ConfigErrors, ConfigOverride, ConfigOverrides},
+    headless::{build_app, OutputSuffix, RunLength, RunLengthArgs},
+    metrics::csv::quote,
+    rng::SeedOverride,
+    time::{init_start_date, DailyTick, GameDate},
+    worlds::{
+        population::components::{CitizenOf, Female, Starving},
+        stability::components::{Stability, StabilityLabel},
+        SimulationSet, WorldEntity,
+    },
+    SimulationState,
+};
+
+use self::sweep::Sweep;
+
+/// Command line arguments of the batch runner.
+#[derive(Parser, Debug, Clone)]
+#[command(about = "Runs the Third Life simulation for many configs and seeds")]
+pub struct BatchArgs {
+    /// Names of the config folders inside of `assets/config`
+    #[arg(long, num_args = 1.., required = true)]
+    pub configs: Vec<String>,
+    /// Seeds to run every config with. Either a single seed, a range like
+    /// `0..10` or `0..=9`, or a comma separated list of those
+    #[arg(long, num_args = 1.., required = true, value_parser = parse_seeds)]
+    pub seeds: Vec<Seeds>,
+    #[command(flatten)]
+    pub run_length: RunLengthArgs,
+    /// Sweep file, every config and seed is run once for each combination of
+    /// the values in it
+    #[arg(long)]
+    pub sweep: Option<PathBuf>,
+    /// Amount of runs simulated at the same time, defaults to the amount of
+    /// available cores
+    #[arg(long)]
+    pub threads: Option<usize>,
+    /// CSV file the summaries of all runs are written to
+    #[arg(long, default_value = "runs/batch_summary.csv")]
+    pub output: PathBuf,
+    /// Replaces a value of the config files in every run, like
+    /// `--set worlds.Mars.population.size=5000`. Wins over the `THIRD_LIFE__`
+    /// environment variables, the sweep wins over both
+    #[arg(long = "set", value_name = "PATH=VALUE")]
+    pub overrides: Vec<ConfigOverride>,
+}
+
+impl BatchArgs {
+    /// Every config, sweep variant and seed combination, in the order they
+    /// were given.
+    pub fn runs(&self, sweep: Option<&Sweep>) -> Vec<BatchRun> {
+        let variants = sweep.map(Sweep::variants)
+            .unwrap_or_else(|| vec![ConfigOverrides::default()]);
+        let seeds = self.seeds.iter().flat_map(|seeds| seeds.0.iter()).collect::<Vec<_>>();
+        let mut overrides = ConfigOverrides::from_env();
+        overrides.0.extend(self.overrides.iter().cloned());
+        let overrides = &overrides;
+        self.configs.iter()
+            .flat_map(|config| variants.iter().map(move |variant| (config, variant)))
+            .flat_map(|(config, variant)| seeds.iter().map(move |seed| BatchRun {
+                config: config.clone(),
+                seed: **seed,
+                overrides: overrides.clone(),
+                parameters: variant.clone(),
+            }))
+            .collect()
+    }
+}
+
+/// A single run of the batch.
+#[derive(Debug, Clone)]
+pub struct BatchRun {
+    pub config: String,
+    pub seed: u64,
+    /// The `--set` and environment overrides, the same for every run.
+    pub overrides: ConfigOverrides,
+    /// The values of the sweep variant, empty without a sweep.
+    pub parameters: ConfigOverrides,
+}
+
+#[derive(Debug, Clone)]
+pub struct Seeds(pub Vec<u64>);
+
+fn parse_seeds(arg: &str) -> Result<Seeds, String> {
+    let mut seeds = Vec::new();
+    for part in arg.split(',').map(str::trim) {
+        let parse = |s: &str| s.trim().parse::<u64>()
+            .map_err(|err| format!("`{s}` is not a valid seed: {err}"));
+        if let Some((start, end)) = part.split_once("..=") {
+            seeds.extend(parse(start)?..=parse(end)?);
+        } else if let Some((start, end)) = part.split_once("..") {
+            seeds.extend(parse(start)?..parse(end)?);
+        } else {
+            seeds.push(parse(part)?);
+        }
+    }
+    Ok(Seeds(seeds))
+}
+
+/// Outcome of a single world in a single run.
+#[derive(Debug, Clone)]
+pub struct RunSummary {
+    pub config: String,
+    pub seed: u64,
+    pub parameters: Vec<ConfigOverride>,
+    /// The `--set` and environment overrides of the run.
+    pub overrides: Vec<ConfigOverride>,
+    pub world: String,
+    /// Amount of citizens alive at the end of the run.
+    pub final_population: usize,
+    /// Highest amount of citizens that were starving on the same day.
+    pub peak_starvation: usize,
+    /// Game years until the last citizen died, or the whole run if the world
+    /// never died out.
+    pub years_survived: f32,
+    /// Children per woman at the end of the run, `NaN` if there are no women
+    /// left.
+    pub average_children_per_mother: f32,
+    /// Label of the world at the end of the run, see
+    /// [`crate::worlds::stability`].
+    pub stability: StabilityLabel,
+}
+
+impl RunSummary {
+    const HEADER: &'static str = "config,seed,world,final_population,peak_starvation,years_survived,average_children_per_mother,stability,overrides";
+
+    /// Values of the sweep first, so the table is keyed by them.
+    fn to_row(&self) -> String {
+        let average_children_per_mother = match self.average_children_per_mother {
+            v if v.is_nan() => String::new(),
+            v => v.to_string(),
+        };
+        let overrides = self.overrides.iter().map(ToString::to_string).collect::<Vec<_>>();
+        self.parameters.iter()
+            .map(|p| quote(&p.value.to_string()))
+            .chain([format!(
+                "{},{},{},{},{},{},{},{},{}",
+                quote(&self.config), self.seed, quote(&self.world), self.final_population,
+                self.peak_starvation, self.years_survived, average_children_per_mother,
+                self.stability, quote(&overrides.join("; "))
+            )])
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+/// Keeps track of the statistics of every world while the run is going and
+/// pushes a [`RunSummary`] for each of them to `output` once it ends. If the
+/// config of the run could not be loaded its errors end up in `output`
+/// instead.
+pub struct RunSummaryPlugin {
+    pub run: BatchRun,
+    pub output: Arc<Mutex<RunOutput>>,
+}
+
+/// The summaries of the worlds of a run, or the mistakes in its config.
+pub type RunOutput = Result<Vec<RunSummary>, Vec<String>>;
+
+impl Plugin for RunSummaryPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(RunTracker {
+                run: self.run.clone(),
+                output: self.output.clone(),
+                start: NaiveDate::MIN,
+                worlds: BTreeMap::new(),
+            })
+            .add_systems(
+                OnEnter(SimulationState::Running),
+                record_start.after(init_start_date)
+            )
+            .add_systems(DailyTick, track_worlds.after(SimulationSet::Statistics))
+            .add_systems(Last, report_summary);
+    }
+}
+
+#[derive(Resource)]
+struct RunTracker {
+    run: BatchRun,
+    output: Arc<Mutex<RunOutput>>,
+    start: NaiveDate,
+    worlds: BTreeMap<String, WorldTracker>,
+}
+
+#[derive(Default)]
+struct WorldTracker {
+    population: usize,
+    peak_starvation: usize,
+    last_alive: Option<NaiveDate>,
+    average_children_per_mother: f32,
+    stability: StabilityLabel,
+}
+
+fn record_start(mut tracker: ResMut<RunTracker>, game_date: Res<GameDate>) {
+    tracker.start = game_date.date;
+}
+
+fn track_worlds(
+    mut tracker: ResMut<RunTracker>,
+    game_date: Res<GameDate>,
+    colonies: Query<(Entity, &WorldEntity, &Stability)>,
+    citizens: Query<(&CitizenOf, Has<Starving>, Option<&Female>)>,
+) {
+    let mut counts = colonies.iter()
+        .map(|(colony, ..)| (colony, (0, 0, 0, 0)))
+        .collect::<BTreeMap<_, _>>();
+    for (of, starving, female) in &citizens {
+        let Some((population, starvation, women, children)) = counts.get_mut(&of.colony) else {
+            continue;
+        };
+        *population += 1;
+        *starvation += starving as usize;
+        if let Some(female) = female {
+            *women += 1;
+            *children += female.children_had;
+        }
+    }
+
+    for (colony, world, stability) in &colonies {
+        let (population, starvation, women, children) = counts[&colony];
+        let world = tracker.worlds.entry(world.name().to_string()).or_default();
+        world.population = population;
+        world.peak_starvation = world.peak_starvation.max(starvation);
+        world.average_children_per_mother = children as f32 / women as f32;
+        world.stability = stability.label;
+        if population > 0 {
+            world.last_alive = Some(game_date.date);
+        }
+    }
+}
+
+fn report_summary(
+    tracker: Res<RunTracker>,
+    errors: Res<ConfigErrors>,
+    exit: EventReader<AppExit>,
+) {
+    if exit.is_empty() {
+        return;
+    }
+    if !errors.0.is_empty() {
+        *tracker.output.lock().unwrap() = Err(errors.0.iter().map(ToString::to_string).collect());
+        return;
+    }
+    let summaries = tracker.worlds.iter().map(|(name, world)| RunSummary {
+        config: tracker.run.config.clone(),
+        seed: tracker.run.seed,
+        parameters: tracker.run.parameters.0.clone(),
+        overrides: tracker.run.overrides.0.clone(),
+        world: name.clone(),
+        final_population: world.population,
+        peak_starvation: world.peak_starvation,
+        years_survived: world.last_alive
+            .map(|date| (date - tracker.start).num_days() as f32 / 365.25)
+            .unwrap_or(0.),
+        average_children_per_mother: world.average_children_per_mother,
+        stability: world.stability,
+    });
+    if let Ok(output) = tracker.output.lock().unwrap().as_mut() {
+        output.extend(summaries);
+    }
+}
+
+/// Simulates a single run until the end of the `run_length` and returns the
+/// summaries of its worlds.
+///
+/// The variants of a sweep run with the same config and seed at the same
+/// time, so the files they write get an [`OutputSuffix`] with the swept
+/// values.
+pub fn run_single(run: &BatchRun, run_length: RunLength) -> RunOutput {
+    let output = Arc::new(Mutex::new(Ok(Vec::new())));
+    let mut app = build_app(Some(run.config.clone()), run_length);
+    let mut overrides = run.overrides.clone();
+    overrides.0.extend(run.parameters.0.iter().cloned());
+    if !run.parameters.0.is_empty() {
+        app.insert_resource(OutputSuffix(output_suffix(&run.parameters)));
+    }
+    app
+        .insert_resource(SeedOverride(run.seed))
+        .insert_resource(overrides)
+        .add_plugins(RunSummaryPlugin { run: run.clone(), output: output.clone() })
+        .run();
+    let summaries = output.lock().unwrap().clone();
+    summaries
+}
+
+/// Runs every combination of [`BatchArgs::runs`] on `threads` worker threads
+/// and writes all summaries to [`BatchArgs::output`]. Runs with mistakes in
+/// their config, or that panic, are reported and left out of the summary.
+pub fn run_batch(args: &BatchArgs, sweep: Option<&Sweep>) -> io::Result<Vec<RunSummary>> {
+    let runs = Mutex::new(args.runs(sweep).into_iter().enumerate());
+    let summaries = Mutex::new(Vec::new());
+    let run_length = args.run_length.run_length();
+    let threads = args.threads
+        .or_else(|| thread::available_parallelism().ok().map(|n| n.get()))
+        .unwrap_or(1)
+        .max(1);
+
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| loop {
+                let Some((index, run)) = runs.lock().unwrap().next() else {
+                    break;
+                };
+                let name = run_name(&run);
+                let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                    run_single(&run, run_length)
+                }));
+                match result {
+                    Ok(Ok(worlds)) => {
+                        for summary in &worlds {
+                            println!(
+                                "{name} {}: population {}, peak starvation {}, \
+                                survived {:.2} years, {:.2} children per mother, {}",
+                                summary.world, summary.final_population,
+                                summary.peak_starvation, summary.years_survived,
+                                summary.average_children_per_mother, summary.stability
+                            );
+                        }
+                        summaries.lock().unwrap().push((index, worlds));
+                    }
+                    Ok(Err(errors)) => eprintln!(
+                        "{name} could not load its config, it is left out:\n  {}",
+                        errors.join("\n  ")
+                    ),
+                    Err(_) => eprintln!("{name} panicked, it is left out"),
+                }
+            });
+        }
+    });
+
+    let mut summaries = summaries.into_inner().unwrap();
+    summaries.sort_by_key(|(index, _)| *index);
+    let summaries = summaries.into_iter().flat_map(|(_, worlds)| worlds).collect::<Vec<_>>();
+    let paths = sweep.map(Sweep::paths).unwrap_or_default();
+    write_summaries(&args.output, &paths, &summaries)?;
+    Ok(summaries)
+}
+
+/// The swept values as part of a file name, like
+/// `worlds.Earth.government.sanitation_spending=10`.
+fn output_suffix(parameters: &ConfigOverrides) -> String {
+    parameters.0.iter()
+        .map(|p| format!("{}={}", p.path, p.value))
+        .collect::<Vec<_>>()
+        .join("_")
+        .chars()
+        .filter(|c| *c != '"')
+        .map(|c| if c.is_ascii_alphanumeric() || "._=-".contains(c) { c } else { '-' })
+        .collect()
+}
+
+/// Short description of the run for the output of the runner.
+fn run_name(run: &BatchRun) -> String {
+    let parameters = run.parameters.0.iter()
+        .map(|p| format!(" {}={}", p.path, p.value))
+        .collect::<String>();
+    format!("{} seed {}{parameters}", run.config, run.seed)
+}
+
+fn write_summaries(path: &Path, paths: &[String], summaries: &[RunSummary]) -> io::Result<()> {
+    if let Some(folder) = path.parent() {
+        fs::create_dir_all(folder)?;
+    }
+    let mut writer = BufWriter::new(File::create(path)?);
+    let header = paths.iter()
+        .map(|p| quote(p))
+        .chain([RunSummary::HEADER.to_string()])
+        .collect::<Vec<_>>();
+    writeln!(writer, "{}", header.join(","))?;
+    for summary in summaries {
+        writeln!(writer, "{}", summary.to_row())?;
+    }
+    writer.flush()
+}
@@ -0,0 +1,189 @@
+//! Parameter sweeps, which run a batch for every combination of a number of
+//! config values.
+//!
+//! A sweep file is a JSON object that maps dotted config paths, see
+//! [`ConfigOverride`], to the values they should take. Values are either a
+//! list or an inclusive range with a `step`:
+//!
+//! ```json
+//! {
+//!     "worlds.Earth.government.sanitation_spending": [10, 20, 30],
+//!     "worlds.Earth.government.citizen_payout": { "from": 0.1, "to": 0.5, "step": 0.1 }
+//! }
+//! ```
+//!
+//! The Cartesian product of all of them makes up the variants of the sweep.
+
+use std::{collections::BTreeMap, fmt, fs, io, path::Path};
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    config::{
+        config_folder_path, format::read_config_file, ConfigOverride, ConfigOverrides,
+        ConfigurationLoader, ThirdLifeConfig,
+    },
+    worlds::config::{ScenarioConfig, WorldsConfig},
+};
+
+/// The paths of a sweep and the values each of them takes.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Sweep(BTreeMap<String, SweepValues>);
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum SweepValues {
+    List(Vec<Value>),
+    Range { from: f64, to: f64, step: f64 },
+}
+
+impl SweepValues {
+    /// Ranges over whole numbers produce integers, so they can be used for
+    /// integer fields of the config.
+    fn values(&self) -> Vec<Value> {
+        match self {
+            SweepValues::List(values) => values.clone(),
+            SweepValues::Range { from, to, step } => {
+                let integers = [from, to, step].iter().all(|v| v.fract() == 0.);
+                let steps = ((to - from) / step + 1e-9).floor().max(-1.) as i64;
+                (0..=steps)
+                    // INFO: Rounded so steps of 0.1 don't end up as 0.30000000000000004
+                    .map(|i| ((from + i as f64 * step) * 1e9).round() / 1e9)
+                    .map(|v| match integers {
+                        true => Value::from(v as i64),
+                        false => Value::from(v),
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SweepError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    /// A range with a `step` that is zero or negative.
+    Step(String),
+    /// A path that doesn't exist in the config files of the run.
+    Path(String),
+}
+
+impl fmt::Display for SweepError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SweepError::Io(err) => write!(f, "Could not read the sweep file: {err}"),
+            SweepError::Json(err) => write!(f, "The sweep file is not valid: {err}"),
+            SweepError::Step(path) => write!(f, "The range of `{path}` needs a positive step"),
+            SweepError::Path(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for SweepError {}
+
+impl From<io::Error> for SweepError {
+    fn from(err: io::Error) -> Self {
+        SweepError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for SweepError {
+    fn from(err: serde_json::Error) -> Self {
+        SweepError::Json(err)
+    }
+}
+
+impl Sweep {
+    pub fn read(path: &Path) -> Result<Self, SweepError> {
+        let sweep = serde_json::from_str::<Sweep>(&fs::read_to_string(path)?)?;
+        for (path, values) in &sweep.0 {
+            if let SweepValues::Range { step, .. } = values {
+                if *step <= 0. {
+                    return Err(SweepError::Step(path.clone()));
+                }
+            }
+        }
+        Ok(sweep)
+    }
+
+    /// The swept paths, in the order their values appear in the
+    /// [`Self::variants`].
+    pub fn paths(&self) -> Vec<String> {
+        self.0.keys().cloned().collect()
+    }
+
+    /// Every combination of the swept values.
+    pub fn variants(&self) -> Vec<ConfigOverrides> {
+        self.0.iter().fold(vec![ConfigOverrides::default()], |variants, (path, values)| {
+            let values = values.values();
+            variants.iter()
+                .flat_map(|variant| values.iter().map(move |value| {
+                    let mut variant = variant.clone();
+                    variant.0.push(ConfigOverride::new(path.clone(), value.clone()));
+                    variant
+                }))
+                .collect()
+        })
+    }
+
+    /// Makes sure the config files, list entries and fields every path goes
+    /// through exist in the given config folder, so a misspelled world name
+    /// or field is noticed before any run is started.
+    pub fn check_paths(&self, config: &str) -> Result<(), SweepError> {
+        let folder = config_folder_path(config);
+        let mut files = BTreeMap::new();
+        for path in self.0.keys() {
+            let file = path.split('.').next().unwrap_or_default();
+            if !files.contains_key(file) {
+                // INFO: Optional files that are missing are checked as empty
+                // ones, required ones are reported once the run loads them.
+                let value = match read_config_file(&folder, file) {
+                    Ok((file_name, value)) => value.map_err(|err| SweepError::Path(format!(
+                        "{file_name} of {config} is not valid: {err}"
+                    )))?,
+                    Err(err) if err.kind() == io::ErrorKind::NotFound => Value::Object(Default::default()),
+                    Err(err) => return Err(SweepError::Path(format!(
+                        "`{path}` targets {file} of {config}, which can't be read: {err}"
+                    ))),
+                };
+                files.insert(file, value);
+            }
+            let override_ = ConfigOverride::new(path.clone(), Value::Null);
+            override_.apply(files.get_mut(file).unwrap())
+                .map_err(|err| SweepError::Path(format!("{config}: {err}")))?;
+        }
+
+        // INFO: Applying an override adds the fields that are missing, so
+        // misspelled fields only show up as unknown ones afterwards.
+        for (file, value) in files {
+            let unknown = match file {
+                f if f == ThirdLifeConfig::path_with_name() => unknown_fields::<ThirdLifeConfig>(value),
+                f if f == WorldsConfig::path_with_name() => unknown_fields::<WorldsConfig>(value),
+                f if f == ScenarioConfig::path_with_name() => unknown_fields::<ScenarioConfig>(value),
+                _ => return Err(SweepError::Path(format!(
+                    "{config}: there is no config file called {file}"
+                ))),
+            };
+            if !unknown.is_empty() {
+                return Err(SweepError::Path(format!(
+                    "{config}: {file} has no field {}",
+                    unknown.iter().map(|path| format!("`{path}`")).collect::<Vec<_>>().join(", ")
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Json paths of the fields in `value` that `T` doesn't have, checked the
+/// same way as when the file is loaded.
+fn unknown_fields<T: ConfigurationLoader>(mut value: Value) -> Vec<String> {
+    // INFO: Mistakes in the templates are reported once the runs load the
+    // file, here they would only hide the unknown fields.
+    let _ = T::resolve(&mut value);
+    let mut unknown = Vec::new();
+    T::unknown_fields(&value, "", &mut unknown);
+    unknown
+}
@@ -0,0 +1,42 @@
+use std::process::exit;
+
+use clap::Parser;
+use third_life::{
+    batch::{run_batch, sweep::Sweep, BatchArgs},
+    config::config_folder_path,
+};
+
+fn main() {
+    let args = BatchArgs::parse();
+    for config in &args.configs {
+        let folder = config_folder_path(config);
+        if !folder.is_dir() {
+            eprintln!("Config folder {} does not exist", folder.display());
+            exit(1);
+        }
+    }
+
+    let sweep = args.sweep.as_ref().map(|path| {
+        let sweep = Sweep::read(path)
+            .and_then(|sweep| args.configs.iter()
+                .try_for_each(|config| sweep.check_paths(config))
+                .map(|_| sweep)
+            );
+        sweep.unwrap_or_else(|err| {
+            eprintln!("{}: {err}", path.display());
+            exit(1);
+        })
+    });
+
+    let runs = args.runs(sweep.as_ref()).len();
+    match run_batch(&args, sweep.as_ref()) {
+        Ok(summaries) => println!(
+            "Finished {runs} runs, wrote {} summaries to {}",
+            summaries.len(), args.output.display()
+        ),
+        Err(err) => {
+            eprintln!("Could not write {}: {err}", args.output.display());
+            exit(1);
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use std::process::exit;
+
+use bevy::{log::LogPlugin, prelude::*};
+use clap::Parser;
+use third_life::{
+    config::config_folder_path,
+    headless::{build_app, ConfigFailed, HeadlessArgs, SnapshotOnEnd},
+};
+
+fn main() {
+    let args = HeadlessArgs::parse();
+    if let Some(config) = &args.config {
+        let folder = config_folder_path(config);
+        if !folder.is_dir() {
+            eprintln!("Config folder {} does not exist", folder.display());
+            exit(1);
+        }
+    }
+
+    let mut app = build_app(args.config.clone(), args.run_length());
+    if let Err(err) = args.simulation.insert_resources(&mut app) {
+        eprintln!("{err}");
+        exit(1);
+    }
+    if let Some(path) = &args.save_snapshot {
+        app.insert_resource(SnapshotOnEnd(path.clone()));
+    }
+    let config_failed = app.world.resource::<ConfigFailed>().clone();
+    app
+        .add_plugins(LogPlugin {
+            level: bevy::log::Level::INFO,
+            ..default()
+        })
+        .run();
+    if config_failed.is_set() {
+        exit(1);
+    }
+}
@@ -0,0 +1,32 @@
+use std::{path::PathBuf, process::exit};
+
+use clap::Parser;
+use third_life::{
+    config::ConfigSchemas,
+    headless::{build_app, RunLength},
+};
+
+/// Writes the json schemas of all config files, so editors can check and
+/// complete them.
+#[derive(Parser, Debug)]
+#[command(about = "Writes the json schemas of the Third Life config files")]
+struct SchemasArgs {
+    /// Folder the schemas are written to
+    #[arg(long, default_value = "schemas")]
+    output: PathBuf,
+}
+
+fn main() {
+    let args = SchemasArgs::parse();
+    // INFO: The schemas are registered while the plugins are added, the app
+    // never has to run.
+    let app = build_app(None, RunLength::Days(0));
+    let schemas = app.world.resource::<ConfigSchemas>();
+    if let Err(err) = schemas.write(&args.output) {
+        eprintln!("Could not write the schemas to {}: {err}", args.output.display());
+        exit(1);
+    }
+    for name in schemas.0.keys() {
+        println!("{}", args.output.join(format!("{name}.schema.json")).display());
+    }
+}
@@ -0,0 +1,57 @@
+//! Command line options shared by every entry point of the simulation.
+
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+use clap::Args;
+
+use crate::{
+    config::{ConfigOverride, ConfigOverrides, SelectedConfigPath},
+    rng::SeedOverride,
+    worlds::snapshot::{LoadedSnapshot, Snapshot, SnapshotError},
+};
+
+/// Options that change how a simulation runs, independent of whether it
+/// runs with a window or not.
+#[derive(Args, Debug, Clone, Default)]
+pub struct SimulationArgs {
+    /// Seed for all random decisions, overrides the `seed` of `config.json`.
+    /// Has no effect when continuing from a snapshot
+    #[arg(long)]
+    pub seed: Option<u64>,
+    /// Continues the run saved in the given snapshot file instead of starting
+    /// a new one, the config folder of the snapshot is used
+    #[arg(long)]
+    pub snapshot: Option<PathBuf>,
+    /// Replaces a value of the config files, like
+    /// `--set worlds.Mars.population.size=5000`. Worlds are picked by their
+    /// name. Wins over the `THIRD_LIFE__` environment variables. When
+    /// continuing from a snapshot, only the government, environment and
+    /// seasons of a world can still be changed
+    #[arg(long = "set", value_name = "PATH=VALUE")]
+    pub overrides: Vec<ConfigOverride>,
+}
+
+impl SimulationArgs {
+    /// Inserts the resources through which the options reach the simulation.
+    /// Fails if the snapshot can't be read.
+    ///
+    /// The [`ConfigOverrides`] of a snapshot are applied again, followed by
+    /// the ones of the environment and the command line.
+    pub fn insert_resources(&self, app: &mut App) -> Result<(), SnapshotError> {
+        if let Some(seed) = self.seed {
+            app.insert_resource(SeedOverride(seed));
+        }
+        let mut overrides = ConfigOverrides::default();
+        if let Some(path) = &self.snapshot {
+            let snapshot = Snapshot::read(path)?;
+            overrides.0.extend(snapshot.overrides.iter().cloned());
+            app.insert_resource(SelectedConfigPath(snapshot.config_path.clone()));
+            app.insert_resource(LoadedSnapshot(snapshot));
+        }
+        overrides.0.extend(ConfigOverrides::from_env().0);
+        overrides.0.extend(self.overrides.iter().cloned());
+        app.insert_resource(overrides);
+        Ok(())
+    }
+}
@@ -1,7 +1,7 @@
-use rand::Rng;
+use rand::{distributions::WeightedIndex, prelude::*};
+use rnglib::RNG;
 
-pub fn roll_chance(chance: u8) -> bool {
-    let mut rng = rand::thread_rng();
+pub fn roll_chance(chance: u8, rng: &mut impl Rng) -> bool {
     let roll = rng.gen_range(0..=100);
     if chance == 0 {
         false
@@ -9,3 +9,45 @@ pub fn roll_chance(chance: u8) -> bool {
         roll <= chance
     }
 }
+
+/// Same as [`RNG::generate_name`] but all syllables are drawn from the given
+/// rng instead of the thread rng, so that names can be reproduced.
+pub fn generate_name(names: &RNG, rng: &mut impl Rng) -> String {
+    let counts = [2, 3, 4, 5];
+    let weights = WeightedIndex::new([4, 10, 3, 1]).unwrap();
+    let mut syllable_count = counts[weights.sample(rng)];
+
+    let mut last = names.prefixes.all().choose(rng).unwrap().clone();
+    let mut name = last.value.clone();
+    while syllable_count > 2 {
+        last = names.centers.filter_from(last.jnext).all().choose(rng).unwrap().clone();
+        name.push_str(&last.value);
+        syllable_count -= 1;
+    }
+    let suffix = names.suffixes.filter_from(last.jnext).all().choose(rng).unwrap().clone();
+    name.push_str(&suffix.value);
+
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => name,
+    }
+}
+
+/// Serde helpers for floats that can be `NaN`. Json has no `NaN` so it is
+/// written as `null` and read back the same way.
+pub mod nan_as_null {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &f32, serializer: S) -> Result<S::Ok, S::Error> {
+        if value.is_nan() {
+            serializer.serialize_none()
+        } else {
+            serializer.serialize_f32(*value)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f32, D::Error> {
+        Ok(Option::<f32>::deserialize(deserializer)?.unwrap_or(f32::NAN))
+    }
+}
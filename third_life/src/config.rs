@@ -13,7 +13,7 @@
 //! a struct are optional then a `def_conf` function will be created for the struct
 //! which makes providing defaults easier.
 //!
-//! ```
+//! ```ignore
 //!#[derive(Resource, Debug, Deserialize, Clone, ConfigFile, Config)]
 //! pub struct ThirdLifeConfig {
 //!     #[def(1.)]
@@ -28,7 +28,7 @@
 //!     }
 //! }
 //!
-//! #[derive(Config, Debug, Deserialize, Clone)]
+//! #[derive(Config, Debug, Serialize, Deserialize, Clone)]
 //! pub struct StartingDate {
 //!     #[def(1.)]
 //!     day: Option<f32>,
@@ -41,17 +41,31 @@
 
 
 extern crate proc_macro;
+pub mod editor;
+pub mod error;
+pub mod format;
+pub mod shape;
+
 use core::panic;
-use std::{collections::HashMap, fs, fmt::Debug};
+use std::{collections::{BTreeMap, HashMap}, fs, fmt::{self, Debug}, marker::PhantomData, path::{Path, PathBuf}, str::FromStr};
 
 
-use bevy::{prelude::*, asset::{AssetLoader, io::Reader, LoadContext, AsyncReadExt}, utils::{thiserror::Error, BoxedFuture}};
-use bevy_egui::{egui::{Window}, EguiContexts};
+use bevy::{prelude::*, ecs::system::SystemParam, asset::{AssetLoader, LoadState, io::{Reader, file::FileAssetReader}, LoadContext, AsyncReadExt}, utils::{thiserror::Error, BoxedFuture}};
+use bevy_egui::{egui::{Color32, Window}, EguiContexts};
 use proc_macros::{Config, ConfigFile};
-use serde::{Deserialize, de::DeserializeOwned};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::Value;
+use chrono::NaiveDate;
 
 use crate::SimulationState;
 
+use self::{
+    editor::ConfigEditor,
+    error::{ConfigError, ConfigErrors, ConfigViolation},
+    format::{config_file_name, ConfigFormat},
+    shape::DescribeConfig,
+};
+
 /// Takes care of registering any configuration that needs to be loaded and then
 /// waits for all of them to load before letting the Simulation begin.
 ///
@@ -72,12 +86,17 @@ impl Plugin for ConfigurationPlugin {
         app
             .init_resource::<AllConfigReaders>()
             .init_resource::<LoadingConfigFileAssets>()
+            .init_resource::<LoadedConfigFileAssets>()
+            .init_resource::<UnappliedConfigChanges>()
+            .init_resource::<ConfigOverrides>()
+            .init_resource::<ConfigErrors>()
             .add_event::<RegisterConfigReaderEvent>()
             .add_event::<ConfigReaderFinishedEvent>()
             .init_asset_loader::<ConfigFileAssetLoader>()
             .init_asset::<ConfigFileAsset>()
-            .add_systems(Update, (show_config_selection).run_if(
+            .add_systems(Update, (skip_config_selection).run_if(
                     in_state(SimulationState::ConfigSelection)
+                        .and_then(resource_exists::<SelectedConfigPath>)
             ))
             .add_systems(Update, (register_readers).run_if(
                 in_state(SimulationState::ConfigSelection).or_else(in_state(SimulationState::LoadingConfig))
@@ -91,10 +110,21 @@ impl Plugin for ConfigurationPlugin {
 
 /// Displayes all folders in `assets/config` as selectable configurations to
 /// the user.
-fn show_config_selection(
+///
+/// Registered by [`crate::ui::ThridLifeUiPlugin`] since it needs egui, runs
+/// only as long as no [`SelectedConfigPath`] has been provided.
+///
+/// If the last selected configuration could not be loaded its
+/// [`ConfigErrors`] are listed below the options.
+///
+/// Every configuration can also be opened in the [`ConfigEditor`] or cloned
+/// into a new one.
+pub(crate) fn show_config_selection(
     mut contexts: EguiContexts,
     mut commands: Commands,
-    mut sim_state: ResMut<NextState<SimulationState>>
+    mut sim_state: ResMut<NextState<SimulationState>>,
+    mut errors: ResMut<ConfigErrors>,
+    mut editor: ResMut<ConfigEditor>,
 ) {
     let config_options = fs::read_dir("assets/config").unwrap();
     Window::new("Select a config file").show(contexts.ctx_mut(), |ui| {
@@ -103,14 +133,39 @@ fn show_config_selection(
                 continue;
             };
             let name = dir.unwrap().file_name().to_string_lossy().to_string();
-            if ui.button(name.clone()).clicked() {
-                sim_state.set(SimulationState::LoadingConfig);
-                commands.insert_resource(SelectedConfigPath::new_std(name));
+            ui.horizontal(|ui| {
+                if ui.button(name.clone()).clicked() {
+                    sim_state.set(SimulationState::LoadingConfig);
+                    commands.insert_resource(SelectedConfigPath::new_std(name.clone()));
+                    errors.0.clear();
+                }
+                if ui.small_button("Edit").clicked() {
+                    editor.open(&name);
+                }
+                if ui.small_button("Clone").clicked() {
+                    editor.start_cloning(&name);
+                }
+            });
+        }
+        if !errors.0.is_empty() {
+            ui.separator();
+            ui.colored_label(Color32::RED, "The selected config could not be loaded:");
+            for error in errors.0.iter() {
+                ui.colored_label(Color32::RED, error.to_string());
             }
         }
     });
 }
 
+/// If the [`SelectedConfigPath`] was already provided before the selection
+/// window could be shown, for example through the command line, the loading
+/// is started right away.
+fn skip_config_selection(
+    mut sim_state: ResMut<NextState<SimulationState>>
+) {
+    sim_state.set(SimulationState::LoadingConfig);
+}
+
 #[derive(Resource)]
 pub struct SelectedConfigPath(pub String);
 
@@ -118,6 +173,19 @@ impl SelectedConfigPath {
     pub fn new_std(folder: String) -> Self {
         Self(format!("config/{folder}"))
     }
+
+    /// Name of the config folder, without the `config/` prefix.
+    pub fn name(&self) -> &str {
+        self.0.rsplit('/').next().unwrap_or(&self.0)
+    }
+}
+
+/// Path on disk of the config folder with the given name. Follows the same
+/// rules as the [`AssetServer`] to find the `assets` folder.
+pub fn config_folder_path(folder: &str) -> PathBuf {
+    FileAssetReader::get_base_path()
+        .join("assets")
+        .join(SelectedConfigPath::new_std(folder.to_string()).0)
 }
 
 /// Hashmap that contains all of the registered config loaders and whether they
@@ -161,10 +229,17 @@ fn register_readers(
 }
 
 /// Recives finished loading events
+///
+/// Once every reader has answered the simulation starts, unless some of them
+/// ran into [`ConfigErrors`]. Then the selection is undone so another
+/// configuration can be picked.
 fn recive_config_loaded_events(
+    mut commands: Commands,
     mut all: ResMut<AllConfigReaders>,
     mut events: EventReader<ConfigReaderFinishedEvent>,
-    mut sim_state: ResMut<NextState<SimulationState>>
+    mut sim_state: ResMut<NextState<SimulationState>>,
+    mut loading_assets: ResMut<LoadingConfigFileAssets>,
+    errors: Res<ConfigErrors>,
 ) {
     for event in events.read() {
         println!("finished loading {}", event.0);
@@ -187,9 +262,20 @@ fn recive_config_loaded_events(
         }
     }
 
-    if all.iter().all(|(_, e)|e.eq(&LoadingReader::Recived)) {
+    if !all.iter().all(|(_, e)|e.eq(&LoadingReader::Recived)) {
+        return;
+    }
+    if errors.0.is_empty() {
         sim_state.set(SimulationState::Running);
+        return;
     }
+    for error in errors.0.iter() {
+        error!("{error}");
+    }
+    all.values_mut().for_each(|reader| *reader = LoadingReader::Waiting);
+    loading_assets.files.clear();
+    commands.remove_resource::<SelectedConfigPath>();
+    sim_state.set(SimulationState::ConfigSelection);
 }
 
 #[derive(Resource, Default)]
@@ -197,6 +283,13 @@ struct LoadingConfigFileAssets {
     files: HashMap<String, Handle<ConfigFileAsset>>
 }
 
+/// Handles of the config files that were loaded, kept so that the files stay
+/// loaded and changes to them are noticed, see [`ConfigurationLoader::reload`].
+#[derive(Resource, Default)]
+struct LoadedConfigFileAssets {
+    files: HashMap<String, Handle<ConfigFileAsset>>
+}
+
 #[derive(Asset, TypePath, Debug, Deserialize)]
 struct ConfigFileAsset {
     file: String
@@ -217,13 +310,13 @@ impl AssetLoader for ConfigFileAssetLoader {
     ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
         Box::pin(async move {
             let mut str = String::new();
-            reader.read_to_string(&mut str).await.unwrap();
+            reader.read_to_string(&mut str).await?;
             let asset = ConfigFileAsset { file: str };
             Ok(asset)
         })
     }
     fn extensions(&self) -> &[&str] {
-        &["json"]
+        &["json", "ron", "toml"]
     }
 
 }
@@ -233,19 +326,69 @@ impl AssetLoader for ConfigFileAssetLoader {
 /// simulation is started. The only thing that needs to be implemented is the 
 /// [`Self::path_with_name`] function which provides the trait with a name and path in 
 /// which to find the related documentaiton, it is automatically postfixed
-/// with `.json`. This string is also used for the registering of the config loader.
+/// with `.json`, `.ron` or `.toml`, whichever of the files exists, see
+/// [`ConfigFormat`]. This string is also used for the registering of the config loader.
 ///
 ///
 /// The trait should be used in combination with the [`proc_macros::ConfigFile`]
 /// derive macro.
-pub trait ConfigurationLoader: Sized + DeserializeOwned + Debug + Resource {
+///
+/// A file can only be left out of a config folder if
+/// [`Self::default_if_missing`] provides a config in its place.
+///
+/// Once the simulation is running, changes to the file are loaded right away
+/// and replace the resource, followed by a [`ConfigChanged`] event.
+pub trait ConfigurationLoader:
+    Sized + Serialize + DeserializeOwned + Debug + Resource + ConfigFields + ConfigConstraints
+        + DescribeConfig
+{
     fn path_with_name() -> &'static str;
 
+    /// Prepares the value of the file before it is read into the config,
+    /// after the [`ConfigOverrides`] were applied. Meant for things the
+    /// config itself doesn't know of, like the templates of
+    /// [`crate::worlds::config::WorldsConfig`]. Does nothing by default.
+    fn resolve(_value: &mut Value) -> Result<(), Vec<ConfigViolation>> {
+        Ok(())
+    }
+
+    /// Config used if the file is missing from the selected folder, which
+    /// makes the file optional. Configs where every field has a default can
+    /// return their `def_conf`, see [`proc_macros::Config`]. A missing file
+    /// is a [`ConfigError`] by default.
+    fn default_if_missing() -> Option<Self> {
+        None
+    }
+
     fn add_configuration(app: &mut App) {
+        app.world.get_resource_or_insert_with(ConfigSchemas::default).0
+            .insert(Self::path_with_name(), Self::json_schema());
         app
             .add_systems(Startup, Self::register())
             .add_systems(OnEnter(SimulationState::LoadingConfig), Self::start_loading())
-            .add_systems(Update,  (Self::notify_done()).run_if(in_state(SimulationState::LoadingConfig)));
+            .add_systems(Update,  (Self::notify_done()).run_if(in_state(SimulationState::LoadingConfig)))
+            .add_systems(Update, Self::reload().run_if(in_state(SimulationState::Running)))
+            .add_event::<ConfigChanged<Self>>();
+    }
+
+    /// [JSON Schema](https://json-schema.org) of what the file looks like
+    /// before [`Self::resolve`]. The one of the config itself by default,
+    /// built from its [`DescribeConfig`].
+    fn file_json_schema() -> Value {
+        Self::shape().json_schema()
+    }
+
+    /// The [`Self::file_json_schema`] with the version of the standard and
+    /// the name of the file.
+    fn json_schema() -> Value {
+        let mut schema = Self::file_json_schema();
+        let fields = schema.as_object_mut().unwrap();
+        fields.insert(
+            "$schema".to_string(),
+            Value::from("http://json-schema.org/draft-07/schema#"),
+        );
+        fields.insert("title".to_string(), Value::from(format!("{}.json", Self::path_with_name())));
+        schema
     }
 
     /// Registers the loader so that [`crate::SimulationState`] is only changed
@@ -270,11 +413,10 @@ pub trait ConfigurationLoader: Sized + DeserializeOwned + Debug + Resource {
             asset_server: Res<AssetServer>,
             mut loading_assets: ResMut<LoadingConfigFileAssets>
         | {
-            let handle = asset_server.load(format!(
-                    "{}/{}.json",
-                    selected_config.0.clone(),
-                    Self::path_with_name()
-            ));
+            let file_name = config_file_name(
+                &config_folder_path(selected_config.name()), Self::path_with_name()
+            );
+            let handle = asset_server.load(format!("{}/{file_name}", selected_config.0));
             let name = Self::path_with_name().to_string();
             let None = loading_assets.as_mut()
                 .files.insert(name.clone(), handle)
@@ -296,58 +438,513 @@ pub trait ConfigurationLoader: Sized + DeserializeOwned + Debug + Resource {
     /// the handle is removed from [`LoadingConfigFileAssets`] and a resource of 
     /// the respective type is added to the Simulation.
     ///
+    /// If the file could not be read, can't be parsed, doesn't match the
+    /// config, has fields the config doesn't know of or values that break
+    /// its [`ConfigConstraints`], the mistakes are added to [`ConfigErrors`]
+    /// instead. A missing file is replaced by [`Self::default_if_missing`],
+    /// with the [`ConfigOverrides`] applied to it, if the config has one.
+    ///
     /// Lastly the finished event is cast out.
     fn notify_done() -> impl Fn(
-        Commands, EventWriter<ConfigReaderFinishedEvent>, 
-        ResMut<LoadingConfigFileAssets>, Res<Assets<ConfigFileAsset>>
+        Commands, EventWriter<ConfigReaderFinishedEvent>, ConfigFiles
     ) + Send + Sync {
         |
             mut commands: Commands,
             mut writer: EventWriter<ConfigReaderFinishedEvent>,
-            mut loading_assets: ResMut<LoadingConfigFileAssets>,
-            config_assets: Res<Assets<ConfigFileAsset>>,
+            mut files: ConfigFiles,
         | {
             let conf_name = Self::path_with_name().to_string();
             
-            let Some(handle) = loading_assets.files.get(&conf_name) else {
+            let Some(handle) = files.loading_assets.files.get(&conf_name) else {
                 return;
             };
-            
-            let Some(ConfigFileAsset{ file }) = config_assets.get(handle) else {
+            let file_path = handle.path()
+                .map(|p| p.to_string())
+                .unwrap_or_else(|| format!("{conf_name}.json"));
+
+            let loaded = match files.config_assets.get(handle) {
+                Some(ConfigFileAsset { file }) => Self::from_file(
+                    &file_path, file, &files.overrides
+                ),
+                None if files.asset_server.get_load_state(handle) == Some(LoadState::Failed) => {
+                    let missing = !FileAssetReader::get_base_path()
+                        .join("assets")
+                        .join(&file_path)
+                        .is_file();
+                    match (missing, Self::default_if_missing()) {
+                        (true, Some(default)) => {
+                            info!("{file_path} is missing, its defaults are used");
+                            let text = serde_json::to_string(&default).unwrap();
+                            Self::from_file(&file_path, &text, &files.overrides)
+                        },
+                        (true, None) => Err(vec![ConfigError::new(
+                            &file_path, "the file is missing from the config folder"
+                        )]),
+                        (false, _) => Err(vec![ConfigError::new(&file_path, "the file could not be read")]),
+                    }
+                },
+                None => return,
+            };
+
+            let handle = files.loading_assets.as_mut().files.remove(&conf_name).unwrap();
+            match loaded {
+                Ok(config_resource) => {
+                    commands.insert_resource(config_resource);
+                    files.loaded_assets.files.insert(conf_name, handle);
+                },
+                Err(errors) => files.errors.0.extend(errors),
+            }
+            writer.send(ConfigReaderFinishedEvent::new(Self::path_with_name()));
+        }
+    }
+
+    /// Reads the file again once it was changed on disk, replaces the
+    /// resource and sends out a [`ConfigChanged`] with the previous version.
+    ///
+    /// A file with mistakes is not taken over, its errors are logged and the
+    /// previous version is kept.
+    fn reload() -> impl Fn(
+        Option<ResMut<Self>>, EventWriter<ConfigChanged<Self>>, ConfigFiles
+    ) + Send + Sync {
+        |
+            config: Option<ResMut<Self>>,
+            mut changed: EventWriter<ConfigChanged<Self>>,
+            mut files: ConfigFiles,
+        | {
+            let modified = files.asset_events.read()
+                .filter_map(|event| match event {
+                    AssetEvent::Modified { id } => Some(*id),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            let Some(handle) = files.loaded_assets.files.get(Self::path_with_name()) else {
                 return;
             };
+            if !modified.contains(&handle.id()) {
+                return;
+            }
+            let (Some(mut config), Some(ConfigFileAsset { file })) = (
+                config, files.config_assets.get(handle)
+            ) else {
+                return;
+            };
+            let file_path = handle.path()
+                .map(|p| p.to_string())
+                .unwrap_or_else(|| format!("{}.json", Self::path_with_name()));
 
-            loading_assets.as_mut().files.remove(&conf_name);
+            match Self::from_file(&file_path, file, &files.overrides) {
+                Ok(new) => {
+                    info!("Reloaded {file_path}");
+                    let previous = std::mem::replace(config.as_mut(), new);
+                    changed.send(ConfigChanged { previous });
+                },
+                Err(errors) => for error in errors {
+                    error!("{error}, the previous version is kept");
+                },
+            }
+        }
+    }
 
-            let config_resource = serde_json::from_str::<Self>(&file).expect(r#"\n
-                The file parsed file contains a mistake and could thus not be
-                parsed plase check that the formatting of the file is correct and
-                matches the type you are trying to parse it to!\n
-            "#);
+    /// Reads the config out of the text of its file, in the [`ConfigFormat`]
+    /// of its extension, after applying the [`ConfigOverrides`] and
+    /// [`Self::resolve`].
+    fn from_file(
+        file_path: &str, text: &str, overrides: &ConfigOverrides
+    ) -> Result<Self, Vec<ConfigError>> {
+        let format = ConfigFormat::of_path(file_path);
+        let mut config_value = format.parse(file_path, text).map_err(|err| vec![err])?;
+        overrides.apply(Self::path_with_name(), &mut config_value)
+            .map_err(|err| vec![ConfigError::new(
+                file_path, format!("the config overrides could not be applied: {err}")
+            )])?;
+        let unresolved = config_value.clone();
+        Self::resolve(&mut config_value).map_err(|violations| violations.into_iter()
+            .map(|v| ConfigError::at(file_path, text, v.path, v.message))
+            .collect::<Vec<_>>())?;
+        let resolved = config_value != unresolved;
 
-            commands.insert_resource(config_resource);
-            writer.send(ConfigReaderFinishedEvent::new(Self::path_with_name()));
+        let mut unknown = Vec::new();
+        Self::unknown_fields(&config_value, "", &mut unknown);
+        if !unknown.is_empty() {
+            let mut errors = unknown.into_iter()
+                .map(|path| ConfigError::unknown_field(file_path, text, path))
+                .collect::<Vec<_>>();
+            errors.sort_by_key(|e| e.line);
+            return Err(errors);
+        }
+
+        let config = serde_json::from_value::<Self>(config_value).map_err(|err| {
+            // INFO: Values don't know where they came from, json files are
+            // read again to find the line. If that works the mistake came from
+            // the overrides. Once resolving changed the value the text doesn't
+            // match it anymore.
+            vec![match (format, resolved) {
+                (ConfigFormat::Json, false) => match serde_json::from_str::<Self>(text) {
+                    Err(err) => ConfigError::from_json(file_path, text, &err),
+                    Ok(_) => ConfigError::new(
+                        file_path, format!("the config overrides don't fit the config: {err}")
+                    ),
+                },
+                _ => ConfigError::new(file_path, err.to_string()),
+            }]
+        })?;
+
+        config.validate().map_err(|violations| violations.into_iter()
+            .map(|v| ConfigError::at(file_path, text, v.path, v.message))
+            .collect::<Vec<_>>())?;
+        Ok(config)
+    }
+
+}
+
+/// Everything [`ConfigurationLoader::notify_done`] and
+/// [`ConfigurationLoader::reload`] need to turn a loaded file into its config.
+#[derive(SystemParam)]
+pub struct ConfigFiles<'w, 's> {
+    loading_assets: ResMut<'w, LoadingConfigFileAssets>,
+    loaded_assets: ResMut<'w, LoadedConfigFileAssets>,
+    config_assets: Res<'w, Assets<ConfigFileAsset>>,
+    asset_events: EventReader<'w, 's, AssetEvent<ConfigFileAsset>>,
+    asset_server: Res<'w, AssetServer>,
+    overrides: Res<'w, ConfigOverrides>,
+    errors: ResMut<'w, ConfigErrors>,
+}
+
+/// The [`ConfigurationLoader::json_schema`] of every config file, by the
+/// name of the file. Filled while the plugins are added.
+#[derive(Resource, Debug, Default)]
+pub struct ConfigSchemas(pub BTreeMap<&'static str, Value>);
+
+impl ConfigSchemas {
+    /// Writes every schema to `<name>.schema.json` in the given folder.
+    pub fn write(&self, folder: &Path) -> std::io::Result<()> {
+        fs::create_dir_all(folder)?;
+        for (name, schema) in &self.0 {
+            let text = serde_json::to_string_pretty(schema).map_err(std::io::Error::from)?;
+            fs::write(folder.join(format!("{name}.schema.json")), text + "\n")?;
+        }
+        Ok(())
+    }
+}
+
+/// Sent after the config file of `T` changed while the simulation runs. The
+/// resource already holds the new version at that point.
+#[derive(Event)]
+pub struct ConfigChanged<T: ConfigurationLoader> {
+    pub previous: T,
+}
+
+/// Changes to config files that could not be applied to the running
+/// simulation and only take effect on the next run, like the starting size
+/// of a population.
+#[derive(Resource, Debug, Default)]
+pub struct UnappliedConfigChanges(pub Vec<String>);
+
+/// Finds the fields of a config file that are not part of the config, which
+/// would otherwise be ignored without notice. Implemented by the
+/// [`proc_macros::Config`] derive.
+pub trait ConfigFields {
+    /// Adds the json path of every field in `value` that doesn't exist on
+    /// the config, or on the configs nested inside of it, to `unknown`.
+    /// `path` is the json path of `value` itself.
+    fn unknown_fields(value: &Value, path: &str, unknown: &mut Vec<String>);
+}
+
+impl<T: ConfigFields> ConfigFields for Option<T> {
+    fn unknown_fields(value: &Value, path: &str, unknown: &mut Vec<String>) {
+        T::unknown_fields(value, path, unknown);
+    }
+}
+
+impl<T: ConfigFields> ConfigFields for Vec<T> {
+    fn unknown_fields(value: &Value, path: &str, unknown: &mut Vec<String>) {
+        for (index, entry) in value.as_array().into_iter().flatten().enumerate() {
+            T::unknown_fields(entry, &format!("{path}[{index}]"), unknown);
+        }
+    }
+}
+
+/// Checks the values of a config against the constraints they have to
+/// follow. Implemented by the [`proc_macros::Config`] derive out of its
+/// `range`, `min` and `validate` attributes.
+pub trait ConfigConstraints {
+    /// Adds every constraint the config, or the configs nested inside of it,
+    /// doesn't follow to `violations`. `path` is the json path of the config
+    /// itself.
+    fn violations(&self, path: &str, violations: &mut Vec<ConfigViolation>);
+
+    /// Checks the constraints of the config and of all the configs nested
+    /// inside of it.
+    fn validate(&self) -> Result<(), Vec<ConfigViolation>> {
+        let mut violations = Vec::new();
+        self.violations("", &mut violations);
+        match violations.is_empty() {
+            true => Ok(()),
+            false => Err(violations),
+        }
+    }
+}
+
+impl<T: ConfigConstraints> ConfigConstraints for Option<T> {
+    fn violations(&self, path: &str, violations: &mut Vec<ConfigViolation>) {
+        if let Some(config) = self {
+            config.violations(path, violations);
+        }
+    }
+}
+
+impl<T: ConfigConstraints> ConfigConstraints for Vec<T> {
+    fn violations(&self, path: &str, violations: &mut Vec<ConfigViolation>) {
+        for (index, entry) in self.iter().enumerate() {
+            entry.violations(&format!("{path}[{index}]"), violations);
         }
     }
+}
+
+/// Json path of the field `key` inside of the value at `path`.
+pub fn field_path(path: &str, key: &str) -> String {
+    match path {
+        "" => key.to_string(),
+        path => format!("{path}.{key}"),
+    }
+}
+
+/// Lets the [`proc_macros::Config`] derive check every field, whether its
+/// type implements [`ConfigFields`] or not. Calling `unknown_fields` on a
+/// `&FieldsOf<T>` picks [`NestedFields`] if it can and falls back to
+/// [`PlainFields`], which does nothing.
+#[doc(hidden)]
+pub struct FieldsOf<T>(PhantomData<T>);
 
+impl<T> FieldsOf<T> {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+#[doc(hidden)]
+pub trait NestedFields {
+    fn unknown_fields(&self, value: &Value, path: &str, unknown: &mut Vec<String>);
+}
+
+impl<T: ConfigFields> NestedFields for FieldsOf<T> {
+    fn unknown_fields(&self, value: &Value, path: &str, unknown: &mut Vec<String>) {
+        T::unknown_fields(value, path, unknown);
+    }
 }
 
+#[doc(hidden)]
+pub trait PlainFields {
+    fn unknown_fields(&self, value: &Value, path: &str, unknown: &mut Vec<String>);
+}
+
+impl<T> PlainFields for &FieldsOf<T> {
+    fn unknown_fields(&self, _: &Value, _: &str, _: &mut Vec<String>) {}
+}
+
+/// Same as [`FieldsOf`] for [`ConfigConstraints`], picks
+/// [`NestedConstraints`] if the value implements it and otherwise
+/// [`PlainConstraints`], which does nothing.
+#[doc(hidden)]
+pub struct ConstraintsOf<'a, T>(pub &'a T);
+
+#[doc(hidden)]
+pub trait NestedConstraints {
+    fn violations(&self, path: &str, violations: &mut Vec<ConfigViolation>);
+}
+
+impl<T: ConfigConstraints> NestedConstraints for ConstraintsOf<'_, T> {
+    fn violations(&self, path: &str, violations: &mut Vec<ConfigViolation>) {
+        self.0.violations(path, violations);
+    }
+}
+
+#[doc(hidden)]
+pub trait PlainConstraints {
+    fn violations(&self, path: &str, violations: &mut Vec<ConfigViolation>);
+}
+
+impl<T> PlainConstraints for &ConstraintsOf<'_, T> {
+    fn violations(&self, _: &str, _: &mut Vec<ConfigViolation>) {}
+}
+
+/// Values that replace the ones of the config files while they are loaded,
+/// without touching the files themselves. Used for example by the parameter
+/// sweeps of [`crate::batch`], the `--set` option of [`crate::cli`] and
+/// the [`CONFIG_OVERRIDE_ENV_PREFIX`] environment variables.
+///
+/// Later overrides win over earlier ones of the same path.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct ConfigOverrides(pub Vec<ConfigOverride>);
+
+/// Environment variables starting with this are read as [`ConfigOverride`]s
+/// by [`ConfigOverrides::from_env`], with `__` between the parts of the path.
+/// `THIRD_LIFE__worlds__Mars__population__size=5000` sets the same value as
+/// `--set worlds.Mars.population.size=5000`.
+pub const CONFIG_OVERRIDE_ENV_PREFIX: &str = "THIRD_LIFE__";
+
+impl ConfigOverrides {
+    /// Every override set through the environment, sorted by their path so
+    /// the order doesn't depend on the environment.
+    pub fn from_env() -> Self {
+        let mut overrides = std::env::vars()
+            .filter_map(|(name, value)| {
+                let path = name.strip_prefix(CONFIG_OVERRIDE_ENV_PREFIX)?.replace("__", ".");
+                Some(ConfigOverride::new(path, ConfigOverride::parse_value(&value)))
+            })
+            .collect::<Vec<_>>();
+        overrides.sort_by(|a, b| a.path.cmp(&b.path));
+        Self(overrides)
+    }
+
+    /// Applies every override that targets the config file with the given
+    /// name, see [`ConfigurationLoader::path_with_name`].
+    pub fn apply(&self, file_name: &str, file: &mut Value) -> Result<(), String> {
+        self.0.iter()
+            .filter(|o| o.file() == file_name)
+            .try_for_each(|o| o.apply(file))
+    }
+}
 
-#[derive(Resource, Debug, Deserialize, Clone, ConfigFile, Config)]
+/// A single value to replace, addressed by a dotted path starting with the
+/// name of the config file, for example `config.seed` or
+/// `worlds.Earth.government.sanitation_spending`.
+///
+/// If the file has a top level field named like the file itself, like the
+/// `worlds` list of `worlds.json`, the path continues inside of that field.
+/// Entries of lists are picked by their `name` field, or by their index if
+/// the segment is a number.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfigOverride {
+    pub path: String,
+    pub value: Value,
+}
+
+impl ConfigOverride {
+    pub fn new(path: impl Into<String>, value: Value) -> Self {
+        Self { path: path.into(), value }
+    }
+
+    /// Reads a value given on the command line or in the environment. It is
+    /// read as json if it can be, like `5000`, `true` or `[1, 2]`, and as a
+    /// text otherwise, so `southern` doesn't need quotes.
+    pub fn parse_value(value: &str) -> Value {
+        serde_json::from_str(value).unwrap_or_else(|_| Value::from(value))
+    }
+
+    /// Name of the config file the override targets.
+    pub fn file(&self) -> &str {
+        self.path.split('.').next().unwrap_or_default()
+    }
+
+    /// Replaces the value at the path, fields that were left out of the file
+    /// are added.
+    pub fn apply(&self, file: &mut Value) -> Result<(), String> {
+        let skip = if file.get(self.file()).is_some() { 0 } else { 1 };
+        let mut segments = self.path.split('.').skip(skip).peekable();
+        if segments.peek().is_none() {
+            return Err(format!("`{}` does not point into the file", self.path));
+        }
+        let mut current = file;
+        while let Some(segment) = segments.next() {
+            let last = segments.peek().is_none();
+            current = match current {
+                Value::Object(fields) if last => {
+                    fields.insert(segment.to_string(), self.value.clone());
+                    return Ok(());
+                }
+                Value::Object(fields) => fields.entry(segment)
+                    .or_insert_with(|| Value::Object(Default::default())),
+                Value::Array(entries) => {
+                    let position = segment.parse::<usize>().ok()
+                        .filter(|i| *i < entries.len())
+                        .or_else(|| entries.iter().position(|e| e["name"] == segment));
+                    let Some(position) = position else {
+                        return Err(format!(
+                            "`{}` has no entry named `{segment}`", self.path
+                        ));
+                    };
+                    if last {
+                        entries[position] = self.value.clone();
+                        return Ok(());
+                    }
+                    &mut entries[position]
+                }
+                _ => return Err(format!(
+                    "`{}` can't look up `{segment}` in a value that is not an object or list",
+                    self.path
+                )),
+            };
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for ConfigOverride {
+    type Err = String;
+
+    /// Reads an override written like `worlds.Mars.population.size=5000`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((path, value)) = s.split_once('=') else {
+            return Err(format!("`{s}` has to look like `path=value`"));
+        };
+        let path = path.trim();
+        if path.split('.').count() < 2 || path.split('.').any(str::is_empty) {
+            return Err(format!(
+                "`{path}` has to start with the name of a config file, like `worlds.Mars.population.size`"
+            ));
+        }
+        Ok(Self::new(path, Self::parse_value(value.trim())))
+    }
+}
+
+impl fmt::Display for ConfigOverride {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.value {
+            Value::String(text) => write!(f, "{}={text}", self.path),
+            value => write!(f, "{}={value}", self.path),
+        }
+    }
+}
+
+#[derive(Resource, Debug, Serialize, Deserialize, Clone, ConfigFile, Config)]
 pub struct ThirdLifeConfig {
     #[def(1.)]
     real_time_day_length: Option<f32>,
     #[def(StartingDate::def_conf())]
-    starting_day: Option<StartingDate>
+    starting_day: Option<StartingDate>,
+    /// Seed of all random decisions, see [`crate::rng::SimRng`]. A random
+    /// one is picked if none is given.
+    #[def(None)]
+    seed: Option<Option<u64>>,
+    /// Where the statistics of the run are exported to, nothing is exported
+    /// if left out. See [`crate::metrics`].
+    #[def(None)]
+    metrics: Option<Option<MetricsConfig>>,
+    /// Per day statistics of every colony written to a single CSV file, see
+    /// [`crate::metrics::time_series`]. Nothing is written if left out.
+    #[def(None)]
+    time_series: Option<Option<TimeSeriesConfig>>,
+    /// When the run stops, see [`crate::end_conditions`].
+    #[def(EndConditionsConfig::def_conf())]
+    end_conditions: Option<EndConditionsConfig>,
+    /// How the worlds are labeled, see [`crate::worlds::stability`].
+    #[def(StabilityConfig::def_conf())]
+    stability: Option<StabilityConfig>,
 }
 
 impl ConfigurationLoader for ThirdLifeConfig {
     fn path_with_name() -> &'static str {
         "config"
     }
+
+    fn default_if_missing() -> Option<Self> {
+        Some(Self::def_conf())
+    }
 }
 
-#[derive(Config, Debug, Deserialize, Clone)]
+#[derive(Config, Debug, Serialize, Deserialize, Clone)]
 pub struct StartingDate {
     #[def(1)]
     day: Option<u32>,
@@ -357,6 +954,102 @@ pub struct StartingDate {
     year: Option<i32>
 }
 
+/// Configuration of the [`crate::metrics::MetricsExportPlugin`].
+///
+/// The `endpoint`, `bucket`, `org` and `token` of the `http` backend can also
+/// be set through the `THIRD_LIFE_METRICS_ENDPOINT`, `THIRD_LIFE_METRICS_BUCKET`,
+/// `THIRD_LIFE_METRICS_ORG` and `THIRD_LIFE_METRICS_TOKEN` environment
+/// variables, which take precedence over the file. Tokens should not be
+/// committed, so the environment is the preferred place for them.
+#[derive(Config, Debug, Serialize, Deserialize, Clone)]
+pub struct MetricsConfig {
+    backend: MetricsBackendKind,
+    /// File the `line_protocol_file` backend writes to, or folder the `csv`
+    /// backend writes to. Relative to the working directory.
+    #[def("metrics".to_string())]
+    path: Option<String>,
+    /// Base url of the InfluxDB server, only plain `http` is supported.
+    #[def(None)]
+    endpoint: Option<Option<String>>,
+    #[def(None)]
+    bucket: Option<Option<String>>,
+    #[def(None)]
+    org: Option<Option<String>>,
+    #[def(None)]
+    token: Option<Option<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MetricsBackendKind {
+    LineProtocolFile,
+    Http,
+    Csv,
+}
+
+impl shape::DescribeConfig for MetricsBackendKind {
+    fn shape() -> shape::ConfigShape {
+        shape::ConfigShape::Choice(vec!["line_protocol_file", "http", "csv"])
+    }
+}
+
+/// Configuration of the [`crate::end_conditions::EndConditionsPlugin`]. The
+/// first condition that is met ends the run.
+#[derive(Config, Debug, Serialize, Deserialize, Clone)]
+pub struct EndConditionsConfig {
+    /// Game date on which the run ends, formatted like `2200-01-01`.
+    #[def(None)]
+    date: Option<Option<NaiveDate>>,
+    /// Ends the run once every colony died out.
+    #[def(true)]
+    total_extinction: Option<bool>,
+    /// Ends the run once any single colony died out.
+    #[def(false)]
+    colony_extinction: Option<bool>,
+    /// Ends the run once the population of any colony falls below this.
+    #[def(None)]
+    min_population: Option<Option<usize>>,
+    /// Folder the final report is written to, relative to the working
+    /// directory.
+    #[def("reports".to_string())]
+    report_directory: Option<String>,
+}
+
+/// Configuration of the [`crate::worlds::stability::StabilityPlugin`].
+#[derive(Config, Debug, Serialize, Deserialize, Clone)]
+pub struct StabilityConfig {
+    /// Amount of past game days the label of a world is based on.
+    #[def(365)]
+    window_days: Option<usize>,
+    /// Amount of game days that have to be tracked before a world is labeled
+    /// as anything but stable, and before anything is estimated.
+    #[def(30)]
+    min_days: Option<usize>,
+    /// Relative change of the population per year below which a world counts
+    /// as stable, `0.01` being one percent.
+    #[def(0.01)]
+    stable_growth: Option<f64>,
+    /// Relative difference between the highest and lowest swing of the
+    /// population around its trend above which a world can be oscillating.
+    #[def(0.05)]
+    oscillation_amplitude: Option<f64>,
+    /// A world with less citizens than this has collapsed.
+    #[def(2)]
+    collapse_population: Option<usize>,
+}
+
+/// Configuration of the [`crate::metrics::time_series::TimeSeriesPlugin`].
+#[derive(Config, Debug, Serialize, Deserialize, Clone)]
+pub struct TimeSeriesConfig {
+    /// Folder the file of the run is written to, relative to the working
+    /// directory.
+    #[def("runs".to_string())]
+    directory: Option<String>,
+    /// Amount of game days after which the written rows are flushed to disk.
+    #[def(30)]
+    flush_interval: Option<u32>,
+}
+
 impl std::ops::Deref for AllConfigReaders {
     type Target = HashMap<String, LoadingReader>;
     fn deref(&self) -> &Self::Target {
@@ -0,0 +1,414 @@
+//! Editing config folders from within the app, opened from the config
+//! selection.
+//!
+//! The editor shows every field of `config.json` and `worlds.json`, with the
+//! default of the fields that are left out, based on the
+//! [`super::shape::DescribeConfig`] of [`ThirdLifeConfig`] and
+//! [`WorldsConfig`]. Files are only written once they pass the same checks
+//! as when they are loaded. Folders can also be cloned into a new config to
+//! start from.
+
+use std::{fs, io};
+
+use bevy::prelude::*;
+use bevy_egui::{
+    egui::{CollapsingHeader, Color32, ComboBox, DragValue, ScrollArea, Ui, Window},
+    EguiContexts,
+};
+use serde_json::Value;
+
+use crate::worlds::config::WorldsConfig;
+
+use super::{
+    config_folder_path,
+    error::ConfigError,
+    format::{config_file_name, ConfigFormat},
+    shape::{ConfigShape, DescribeConfig, FieldShape},
+    ConfigOverrides, ConfigurationLoader, SelectedConfigPath, ThirdLifeConfig,
+};
+
+/// State of the config editor and of the clone wizard.
+#[derive(Resource, Default)]
+pub struct ConfigEditor {
+    open: Option<EditedFolder>,
+    /// Folder that is about to be cloned, with the name of the new one.
+    cloning: Option<(String, String)>,
+    errors: Vec<String>,
+}
+
+impl ConfigEditor {
+    /// Opens the config folder with the given name, reporting files that
+    /// can't be read or aren't json. Files in the other [`ConfigFormat`]s
+    /// are left to text editors, so their comments aren't lost.
+    pub fn open(&mut self, folder: &str) {
+        let files = [
+            EditedFile::read::<ThirdLifeConfig>(folder),
+            EditedFile::read::<WorldsConfig>(folder),
+        ];
+        self.errors.clear();
+        self.open = None;
+        let mut opened = Vec::new();
+        for file in files {
+            match file {
+                Ok(file) => opened.push(file),
+                Err(error) => self.errors.push(error.to_string()),
+            }
+        }
+        if self.errors.is_empty() {
+            self.open = Some(EditedFolder { folder: folder.to_string(), files: opened, saved: false });
+        }
+    }
+
+    /// Starts the wizard that clones the folder with the given name.
+    pub fn start_cloning(&mut self, folder: &str) {
+        self.cloning = Some((folder.to_string(), format!("{folder}-copy")));
+        self.errors.clear();
+    }
+}
+
+struct EditedFolder {
+    folder: String,
+    files: Vec<EditedFile>,
+    /// Whether the files were written since the last edit.
+    saved: bool,
+}
+
+struct EditedFile {
+    /// Path of the file inside of the `assets` folder.
+    path: String,
+    shape: ConfigShape,
+    value: Value,
+    /// Loads the text the way the config is loaded when a run starts.
+    check: fn(&str, &str) -> Result<(), Vec<ConfigError>>,
+}
+
+impl EditedFile {
+    /// A file that doesn't exist is opened empty, every field of it then
+    /// has its default.
+    fn read<T: ConfigurationLoader + DescribeConfig>(folder: &str) -> Result<Self, ConfigError> {
+        let name = config_file_name(&config_folder_path(folder), T::path_with_name());
+        let path = format!("{}/{name}", SelectedConfigPath::new_std(folder.to_string()).0);
+        if ConfigFormat::of_path(&name) != ConfigFormat::Json {
+            return Err(ConfigError::new(&path, "only json files can be edited here, use a text editor"));
+        }
+        let value = match fs::read_to_string(config_folder_path(folder).join(&name)) {
+            Ok(text) => serde_json::from_str(&text)
+                .map_err(|err| ConfigError::from_json(&path, &text, &err))?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Value::Object(Default::default()),
+            Err(err) => return Err(ConfigError::new(&path, err.to_string())),
+        };
+        Ok(Self {
+            path,
+            shape: T::shape(),
+            value,
+            check: |path, text| T::from_file(path, text, &ConfigOverrides::default()).map(|_| ()),
+        })
+    }
+}
+
+/// Shows the editor once a folder was opened and the clone wizard once a
+/// folder is about to be cloned.
+///
+/// Registered by [`crate::ui::ThridLifeUiPlugin`] next to the config
+/// selection.
+pub(crate) fn show_config_editor(
+    mut contexts: EguiContexts,
+    mut editor: ResMut<ConfigEditor>,
+) {
+    let editor = editor.as_mut();
+    let ctx = contexts.ctx_mut();
+
+    if let Some((source, mut name)) = editor.cloning.take() {
+        let (mut cloned, mut cancelled) = (false, false);
+        Window::new(format!("Clone {source}")).show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Name of the new config");
+                ui.text_edit_singleline(&mut name);
+            });
+            ui.horizontal(|ui| {
+                if ui.button("Clone and edit").clicked() {
+                    match clone_folder(&source, &name) {
+                        Ok(()) => cloned = true,
+                        Err(error) => editor.errors = vec![error],
+                    }
+                }
+                cancelled = ui.button("Cancel").clicked();
+            });
+            show_errors(ui, &editor.errors);
+        });
+        if cloned {
+            editor.open(&name);
+        } else if cancelled {
+            editor.errors.clear();
+        } else {
+            editor.cloning = Some((source, name));
+        }
+    }
+
+    let Some(folder) = &mut editor.open else {
+        if editor.cloning.is_none() && !editor.errors.is_empty() {
+            Window::new("Config editor").show(ctx, |ui| {
+                show_errors(ui, &editor.errors);
+                if ui.button("Close").clicked() {
+                    editor.errors.clear();
+                }
+            });
+        }
+        return;
+    };
+    let mut close = false;
+    Window::new(format!("Edit {}", folder.folder)).show(ctx, |ui| {
+        ScrollArea::vertical().max_height(600.).show(ui, |ui| {
+            for file in folder.files.iter_mut() {
+                CollapsingHeader::new(&file.path).default_open(true).show(ui, |ui| {
+                    if edit_value(ui, &file.path, &file.shape, &mut file.value) {
+                        folder.saved = false;
+                    }
+                });
+            }
+        });
+        ui.separator();
+        ui.horizontal(|ui| {
+            if ui.button("Save").clicked() {
+                editor.errors = save_folder(folder);
+                folder.saved = editor.errors.is_empty();
+            }
+            if ui.button("Close").clicked() {
+                close = true;
+            }
+            if folder.saved {
+                ui.label("Saved");
+            }
+        });
+        show_errors(ui, &editor.errors);
+    });
+    if close {
+        editor.open = None;
+        editor.errors.clear();
+    }
+}
+
+fn show_errors(ui: &mut Ui, errors: &[String]) {
+    for error in errors {
+        ui.colored_label(Color32::RED, error);
+    }
+}
+
+/// Copies the files of the config folder `source` into a new folder.
+fn clone_folder(source: &str, name: &str) -> Result<(), String> {
+    if name.is_empty() || name.contains(['/', '\\', '.']) {
+        return Err("The name can't be empty or contain `/`, `\\` or `.`".to_string());
+    }
+    let target = config_folder_path(name);
+    if target.exists() {
+        return Err(format!("A config named `{name}` already exists"));
+    }
+    let copy = || -> io::Result<()> {
+        fs::create_dir_all(&target)?;
+        for entry in fs::read_dir(config_folder_path(source))? {
+            let entry = entry?;
+            if entry.file_type()?.is_file() {
+                fs::copy(entry.path(), target.join(entry.file_name()))?;
+            }
+        }
+        Ok(())
+    };
+    copy().map_err(|err| format!("`{source}` could not be cloned: {err}"))
+}
+
+/// Writes every file of the folder, but only if all of them pass the checks
+/// of the loading. Returns the mistakes otherwise.
+fn save_folder(folder: &EditedFolder) -> Vec<String> {
+    let texts = folder.files.iter()
+        .map(|file| serde_json::to_string_pretty(&file.value).unwrap())
+        .collect::<Vec<_>>();
+    let errors = folder.files.iter().zip(&texts)
+        .filter_map(|(file, text)| (file.check)(&file.path, text).err())
+        .flatten()
+        .map(|error| error.to_string())
+        .collect::<Vec<_>>();
+    if !errors.is_empty() {
+        return errors;
+    }
+    folder.files.iter().zip(texts)
+        .filter_map(|(file, text)| {
+            let name = file.path.rsplit('/').next().unwrap();
+            fs::write(config_folder_path(&folder.folder).join(name), text + "\n")
+                .err()
+                .map(|err| format!("{} could not be written: {err}", file.path))
+        })
+        .collect()
+}
+
+/// Shows the widgets to edit a value of the given shape, `id` has to be
+/// unique within the editor. Returns whether the value was changed.
+fn edit_value(ui: &mut Ui, id: &str, shape: &ConfigShape, value: &mut Value) -> bool {
+    match (shape, &mut *value) {
+        (ConfigShape::Struct(fields), Value::Object(_)) => {
+            let mut changed = false;
+            for field in fields {
+                changed |= edit_field(ui, id, field, value);
+            }
+            changed
+        },
+        (ConfigShape::List(entry_shape), Value::Array(entries)) => {
+            let mut changed = false;
+            let mut removed = None;
+            for (index, entry) in entries.iter_mut().enumerate() {
+                let title = match entry.get("name").and_then(Value::as_str) {
+                    Some(name) => name.to_string(),
+                    None => format!("#{index}"),
+                };
+                let entry_id = format!("{id}[{index}]");
+                CollapsingHeader::new(title).id_source(&entry_id).show(ui, |ui| {
+                    changed |= edit_value(ui, &entry_id, entry_shape, entry);
+                    if ui.button("Remove").clicked() {
+                        removed = Some(index);
+                    }
+                });
+            }
+            if let Some(index) = removed {
+                entries.remove(index);
+                changed = true;
+            }
+            if ui.button("Add").clicked() {
+                entries.push(new_entry(entry_shape, entries));
+                changed = true;
+            }
+            changed
+        },
+        (ConfigShape::Optional(_), Value::Null) => {
+            ui.horizontal(|ui| {
+                ui.weak("none");
+                ui.button("Set").clicked()
+            }).inner.then(|| *value = shape_without_null(shape).skeleton()).is_some()
+        },
+        (ConfigShape::Optional(inner), _) => {
+            let mut changed = edit_value(ui, id, inner, value);
+            if ui.button("Clear").clicked() {
+                *value = Value::Null;
+                changed = true;
+            }
+            changed
+        },
+        (ConfigShape::Pair(first, second), Value::Array(entries)) if entries.len() == 2 => {
+            ui.horizontal(|ui| {
+                let changed = edit_value(ui, &format!("{id}[0]"), first, &mut entries[0]);
+                edit_value(ui, &format!("{id}[1]"), second, &mut entries[1]) || changed
+            }).inner
+        },
+        (ConfigShape::Integer, Value::Number(number)) if number.is_i64() => {
+            let mut current = number.as_i64().unwrap();
+            let changed = ui.add(DragValue::new(&mut current)).changed();
+            if changed {
+                *value = Value::from(current);
+            }
+            changed
+        },
+        (ConfigShape::Number, Value::Number(number)) => {
+            let mut current = number.as_f64().unwrap();
+            let changed = ui.add(DragValue::new(&mut current).speed(0.01)).changed();
+            if changed {
+                *value = Value::from(current);
+            }
+            changed
+        },
+        (ConfigShape::Bool, Value::Bool(current)) => ui.checkbox(current, "").changed(),
+        (ConfigShape::Text | ConfigShape::Date, Value::String(current)) => {
+            ui.text_edit_singleline(current).changed()
+        },
+        (ConfigShape::Choice(options), Value::String(current)) => {
+            let mut changed = false;
+            ComboBox::from_id_source(id).selected_text(current.as_str()).show_ui(ui, |ui| {
+                for option in options {
+                    if ui.selectable_label(current == option, *option).clicked() {
+                        *current = option.to_string();
+                        changed = true;
+                    }
+                }
+            });
+            changed
+        },
+        (ConfigShape::Any, _) => {
+            ui.label(value.to_string());
+            false
+        },
+        _ => {
+            // INFO: The value doesn't fit its field, for example a text where
+            // a number belongs. Nothing to edit it with but it can be reset.
+            ui.horizontal(|ui| {
+                ui.colored_label(Color32::RED, value.to_string());
+                ui.button("Reset").clicked()
+            }).inner.then(|| *value = shape.skeleton()).is_some()
+        },
+    }
+}
+
+/// Shows a field of a struct, fields that are left out show their default
+/// and can be set. Returns whether the struct was changed.
+fn edit_field(ui: &mut Ui, id: &str, field: &FieldShape, object: &mut Value) -> bool {
+    let object = object.as_object_mut().unwrap();
+    let field_id = super::field_path(id, field.name);
+    let nested = matches!(
+        shape_without_null(&field.shape),
+        ConfigShape::Struct(_) | ConfigShape::List(_)
+    );
+
+    let Some(value) = object.get_mut(field.name) else {
+        return ui.horizontal(|ui| {
+            ui.label(field.name);
+            match &field.default {
+                Some(_) if nested => ui.weak("default"),
+                Some(default) => ui.weak(format!("default: {default}")),
+                None => ui.colored_label(Color32::RED, "missing"),
+            };
+            ui.button("Set").clicked()
+        }).inner.then(|| {
+            let value = match &field.default {
+                Some(default) if !nested || !default.is_null() => default.clone(),
+                _ => shape_without_null(&field.shape).skeleton(),
+            };
+            object.insert(field.name.to_string(), value);
+        }).is_some();
+    };
+
+    let mut changed = false;
+    let mut reset = false;
+    if nested {
+        CollapsingHeader::new(field.name).id_source(&field_id).show(ui, |ui| {
+            changed = edit_value(ui, &field_id, &field.shape, value);
+            reset = field.default.is_some() && ui.button("Use default").clicked();
+        });
+    } else {
+        ui.horizontal(|ui| {
+            ui.label(field.name);
+            changed = edit_value(ui, &field_id, &field.shape, value);
+            reset = field.default.is_some() && ui.button("Use default").clicked();
+        });
+    }
+    if reset {
+        object.remove(field.name);
+    }
+    changed || reset
+}
+
+/// The shape inside of any [`ConfigShape::Optional`].
+fn shape_without_null(shape: &ConfigShape) -> &ConfigShape {
+    match shape {
+        ConfigShape::Optional(inner) => shape_without_null(inner),
+        shape => shape,
+    }
+}
+
+/// Entry added to a list, a copy of the last one since most of it probably
+/// stays the same. Entries with a `name` get a new one, which has to be
+/// unique for worlds.
+fn new_entry(shape: &ConfigShape, entries: &[Value]) -> Value {
+    let Some(mut entry) = entries.last().cloned() else {
+        return shape.skeleton();
+    };
+    if let Some(name) = entry.get_mut("name") {
+        *name = Value::from(format!("New {}", entries.len() + 1));
+    }
+    entry
+}
@@ -0,0 +1,211 @@
+//! Errors of config files that could not be loaded, and finding out where in
+//! the file they are.
+
+use std::{error::Error, fmt};
+
+use bevy::prelude::*;
+
+use super::format::ConfigFormat;
+
+/// Every error found while loading the selected config. As long as it isn't
+/// empty the simulation goes back to [`crate::SimulationState::ConfigSelection`]
+/// instead of starting.
+#[derive(Resource, Debug, Default)]
+pub struct ConfigErrors(pub Vec<ConfigError>);
+
+/// A mistake in a config file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigError {
+    /// Path of the file inside of the `assets` folder.
+    pub file: String,
+    /// Json path of the value the error is about, like `worlds[0].food`.
+    /// Empty if it is about the whole file.
+    pub path: String,
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl ConfigError {
+    pub fn new(file: &str, message: impl Into<String>) -> Self {
+        Self { file: file.to_string(), path: String::new(), line: None, message: message.into() }
+    }
+
+    /// Error of [`serde_json`] while reading `text`, which is located through
+    /// the line and column it reports.
+    pub fn from_json(file: &str, text: &str, err: &serde_json::Error) -> Self {
+        let message = err.to_string();
+        // INFO: serde_json puts the position at the end of its messages,
+        // it is shown separately.
+        let message = match message.rfind(" at line ") {
+            Some(at) => message[..at].to_string(),
+            None => message,
+        };
+        let line = (err.line() > 0).then_some(err.line());
+        Self {
+            file: file.to_string(),
+            path: line.map(|line| path_at(text, line, err.column())).unwrap_or_default(),
+            line,
+            message,
+        }
+    }
+
+    /// Error about the value at the json `path` of `text`. The line is only
+    /// looked up in json files.
+    pub fn at(file: &str, text: &str, path: String, message: impl Into<String>) -> Self {
+        Self {
+            file: file.to_string(),
+            line: match ConfigFormat::of_path(file) {
+                ConfigFormat::Json => line_of(text, &path),
+                _ => None,
+            },
+            path,
+            message: message.into(),
+        }
+    }
+
+    /// Error for a field of `text` that is not part of the config.
+    pub fn unknown_field(file: &str, text: &str, path: String) -> Self {
+        Self::at(file, text, path, UNKNOWN_FIELD)
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.file)?;
+        if let Some(line) = self.line {
+            write!(f, ":{line}")?;
+        }
+        if !self.path.is_empty() {
+            write!(f, " at `{}`", self.path)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+impl Error for ConfigError {}
+
+/// Message of a field that is not part of the config.
+pub const UNKNOWN_FIELD: &str = "unknown field, check its spelling and where it is placed";
+
+/// A constraint of a config that is not followed, see
+/// [`super::ConfigConstraints`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigViolation {
+    /// Json path of the value that breaks the constraint.
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.path.as_str() {
+            "" => write!(f, "{}", self.message),
+            path => write!(f, "`{path}` {}", self.message),
+        }
+    }
+}
+
+impl Error for ConfigViolation {}
+
+/// An object or list the json scanner is inside of.
+enum Frame {
+    Object { key: Option<String>, expects_key: bool },
+    List { index: usize },
+}
+
+fn frames_path(frames: &[Frame]) -> String {
+    let mut path = String::new();
+    for frame in frames {
+        match frame {
+            Frame::Object { key: Some(key), .. } => path = super::field_path(&path, key),
+            Frame::Object { key: None, .. } => {},
+            Frame::List { index } => path.push_str(&format!("[{index}]")),
+        }
+    }
+    path
+}
+
+/// Walks through the json text, calling `visit` with the open objects and
+/// lists before every character, and with `true` right after each key that
+/// was read. Stops once `visit` returns `false`.
+///
+/// Mistakes in the json are skipped over, so this still works for files
+/// that [`serde_json`] refuses.
+fn scan(text: &str, mut visit: impl FnMut(&[Frame], usize, usize, bool) -> bool) {
+    let mut frames: Vec<Frame> = Vec::new();
+    let (mut line, mut column) = (1, 1);
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if !visit(&frames, line, column, false) {
+            return;
+        }
+        let (key_line, key_column) = (line, column);
+        if c == '\n' {
+            line += 1;
+            column = 1;
+            continue;
+        }
+        column += 1;
+        match c {
+            '{' => frames.push(Frame::Object { key: None, expects_key: true }),
+            '[' => frames.push(Frame::List { index: 0 }),
+            '}' | ']' => { frames.pop(); },
+            ':' => if let Some(Frame::Object { expects_key, .. }) = frames.last_mut() {
+                *expects_key = false;
+            },
+            ',' => match frames.last_mut() {
+                Some(Frame::Object { key, expects_key }) => {
+                    *key = None;
+                    *expects_key = true;
+                },
+                Some(Frame::List { index }) => *index += 1,
+                None => {},
+            },
+            '"' => {
+                let mut string = String::new();
+                while let Some(c) = chars.next() {
+                    column += 1;
+                    match c {
+                        '"' => break,
+                        '\\' => if let Some(escaped) = chars.next() {
+                            column += 1;
+                            string.push(escaped);
+                        },
+                        '\n' => { line += 1; column = 1; },
+                        c => string.push(c),
+                    }
+                }
+                if let Some(Frame::Object { key, expects_key: true }) = frames.last_mut() {
+                    *key = Some(string);
+                    if !visit(&frames, key_line, key_column, true) {
+                        return;
+                    }
+                }
+            },
+            _ => {},
+        }
+    }
+}
+
+/// Json path of the value that `text` is in right after the character at
+/// the given position, which is where [`serde_json`] points its errors to.
+fn path_at(text: &str, line: usize, column: usize) -> String {
+    let mut path = String::new();
+    scan(text, |frames, l, c, _| {
+        path = frames_path(frames);
+        (l, c) <= (line, column)
+    });
+    path
+}
+
+/// Line of the key with the given json path in `text`.
+fn line_of(text: &str, path: &str) -> Option<usize> {
+    let mut found = None;
+    scan(text, |frames, line, _, is_key| {
+        if is_key && frames_path(frames) == path {
+            found = Some(line);
+        }
+        found.is_none()
+    });
+    found
+}
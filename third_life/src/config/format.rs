@@ -0,0 +1,249 @@
+//! The file formats config files can be written in. Every format is read
+//! into a json [`Value`] first, so the defaults, overrides and checks of the
+//! configs work the same no matter the format.
+
+use std::{fs, io, path::Path};
+
+use serde_json::{Map, Number, Value};
+
+use super::error::ConfigError;
+
+/// Format of a config file, picked by its extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Json,
+    /// [Rusty Object Notation](https://github.com/ron-rs/ron), which allows
+    /// comments.
+    ///
+    /// The names of enum variants are lost when a file is read into a
+    /// [`ron::Value`], `backend: Csv` ends up as an empty value. Enum values
+    /// have to be quoted snake case strings like in json, `backend: "csv"`.
+    Ron,
+    /// [TOML](https://toml.io), which allows comments.
+    Toml,
+}
+
+impl ConfigFormat {
+    /// Every format, in the order they are looked for.
+    pub const ALL: [ConfigFormat; 3] = [ConfigFormat::Json, ConfigFormat::Ron, ConfigFormat::Toml];
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ConfigFormat::Json => "json",
+            ConfigFormat::Ron => "ron",
+            ConfigFormat::Toml => "toml",
+        }
+    }
+
+    /// Format of the file at `path`, json if the extension is unknown.
+    pub fn of_path(path: &str) -> Self {
+        Self::ALL.into_iter()
+            .find(|format| path.ends_with(&format!(".{}", format.extension())))
+            .unwrap_or(ConfigFormat::Json)
+    }
+
+    /// Reads the text of `file` into a json value.
+    pub fn parse(&self, file: &str, text: &str) -> Result<Value, ConfigError> {
+        match self {
+            ConfigFormat::Json => serde_json::from_str(text)
+                .map_err(|err| ConfigError::from_json(file, text, &err)),
+            ConfigFormat::Ron => {
+                let value = ron::from_str::<ron::Value>(text).map_err(|err| ConfigError {
+                    line: Some(err.position.line),
+                    ..ConfigError::new(file, err.code.to_string())
+                })?;
+                ron_to_json(value).map_err(|message| ConfigError::new(file, message))
+            },
+            ConfigFormat::Toml => {
+                let document = text.parse::<toml_edit::Document>().map_err(|err| ConfigError {
+                    line: err.span().map(|span| text[..span.start].matches('\n').count() + 1),
+                    ..ConfigError::new(file, err.message().trim_end().replace('\n', ", "))
+                })?;
+                Ok(toml_item_to_json(document.as_item()))
+            },
+        }
+    }
+}
+
+/// Name of the file of the config `name` inside of `folder`, which is
+/// whichever of `name.json`, `name.ron` or `name.toml` exists. Falls back to
+/// `name.json` if none of them does.
+pub fn config_file_name(folder: &Path, name: &str) -> String {
+    let mut existing = ConfigFormat::ALL.into_iter()
+        .map(|format| format!("{name}.{}", format.extension()))
+        .filter(|file| folder.join(file).is_file());
+    let file = existing.next().unwrap_or_else(|| format!("{name}.json"));
+    for ignored in existing {
+        bevy::log::warn!("{} is ignored, {file} is used instead", folder.join(ignored).display());
+    }
+    file
+}
+
+/// Reads the config `name` out of `folder` on disk, in whichever format it
+/// was written. Returns the name of the file along with its value.
+pub fn read_config_file(folder: &Path, name: &str) -> io::Result<(String, Result<Value, ConfigError>)> {
+    let file = config_file_name(folder, name);
+    let text = fs::read_to_string(folder.join(&file))?;
+    let value = ConfigFormat::of_path(&file).parse(&file, &text);
+    Ok((file, value))
+}
+
+fn ron_to_json(value: ron::Value) -> Result<Value, String> {
+    Ok(match value {
+        ron::Value::Bool(b) => Value::Bool(b),
+        ron::Value::Char(c) => Value::String(c.to_string()),
+        ron::Value::String(s) => Value::String(s),
+        ron::Value::Number(ron::Number::Integer(i)) => Value::from(i),
+        ron::Value::Number(ron::Number::Float(f)) => Number::from_f64(f.get())
+            .map(Value::Number)
+            .ok_or_else(|| format!("{} can't be used as a number", f.get()))?,
+        ron::Value::Option(Some(inner)) => ron_to_json(*inner)?,
+        ron::Value::Option(None) | ron::Value::Unit => Value::Null,
+        ron::Value::Seq(entries) => Value::Array(
+            entries.into_iter().map(ron_to_json).collect::<Result<_, _>>()?
+        ),
+        ron::Value::Map(map) => Value::Object(map.into_iter()
+            .map(|(key, value)| match key {
+                ron::Value::String(key) => Ok((key, ron_to_json(value)?)),
+                key => Err(format!("only names can be used as keys, not {key:?}")),
+            })
+            .collect::<Result<Map<_, _>, _>>()?),
+    })
+}
+
+fn toml_item_to_json(item: &toml_edit::Item) -> Value {
+    match item {
+        toml_edit::Item::None => Value::Null,
+        toml_edit::Item::Value(value) => toml_value_to_json(value),
+        toml_edit::Item::Table(table) => Value::Object(table.iter()
+            .map(|(key, item)| (key.to_string(), toml_item_to_json(item)))
+            .collect()),
+        toml_edit::Item::ArrayOfTables(tables) => Value::Array(tables.iter()
+            .map(|table| Value::Object(table.iter()
+                .map(|(key, item)| (key.to_string(), toml_item_to_json(item)))
+                .collect()))
+            .collect()),
+    }
+}
+
+fn toml_value_to_json(value: &toml_edit::Value) -> Value {
+    match value {
+        toml_edit::Value::String(s) => Value::from(s.value().as_str()),
+        toml_edit::Value::Integer(i) => Value::from(*i.value()),
+        toml_edit::Value::Float(f) => Value::from(*f.value()),
+        toml_edit::Value::Boolean(b) => Value::Bool(*b.value()),
+        // INFO: Dates like `2160-01-01` are written the same way as the
+        // texts that the json configs use for them.
+        toml_edit::Value::Datetime(d) => Value::from(d.value().to_string()),
+        toml_edit::Value::Array(entries) => Value::Array(entries.iter().map(toml_value_to_json).collect()),
+        toml_edit::Value::InlineTable(table) => Value::Object(table.iter()
+            .map(|(key, value)| (key.to_string(), toml_value_to_json(value)))
+            .collect()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        config::{ConfigOverrides, ConfigurationLoader},
+        worlds::config::{ScenarioConfig, WorldsConfig},
+    };
+
+    use super::*;
+
+    const WORLDS_JSON: &str = r#"{
+        "worlds": [
+            {
+                "name": "Earth",
+                "world_position": [-300, 0],
+                "environment": { "urbanization": 0.57 },
+                "seasons": { "hemisphere": "southern" },
+                "sprite": { "sprite_sheet": "terran.png", "frames": 60, "frames_layout": [60, 1], "shape": [48, 48], "animation_timer": 0.1 }
+            }
+        ]
+    }"#;
+
+    const WORLDS_RON: &str = r#"(
+        worlds: [
+            (
+                name: "Earth",
+                world_position: (-300, 0),
+                // Comments are allowed
+                environment: (urbanization: 0.57),
+                seasons: (hemisphere: "southern"),
+                sprite: (sprite_sheet: "terran.png", frames: 60, frames_layout: (60, 1), shape: (48, 48), animation_timer: 0.1),
+            ),
+        ],
+    )"#;
+
+    const WORLDS_TOML: &str = r#"
+        [[worlds]]
+        name = "Earth"
+        world_position = [-300, 0]
+        # Comments are allowed
+        environment = { urbanization = 0.57 }
+        seasons = { hemisphere = "southern" }
+        sprite = { sprite_sheet = "terran.png", frames = 60, frames_layout = [60, 1], shape = [48, 48], animation_timer = 0.1 }
+    "#;
+
+    fn parse(file: &str, text: &str) -> Value {
+        ConfigFormat::of_path(file).parse(file, text).unwrap()
+    }
+
+    /// The config read from `file`, as a value so configs without
+    /// `PartialEq` can be compared.
+    fn load<T: ConfigurationLoader>(file: &str, text: &str) -> Value {
+        let config = T::from_file(file, text, &ConfigOverrides::default()).unwrap();
+        serde_json::to_value(config).unwrap()
+    }
+
+    #[test]
+    fn worlds_are_the_same_in_every_format() {
+        let json = parse("worlds.json", WORLDS_JSON);
+        assert_eq!(parse("worlds.ron", WORLDS_RON), json);
+        assert_eq!(parse("worlds.toml", WORLDS_TOML), json);
+
+        let config = load::<WorldsConfig>("worlds.json", WORLDS_JSON);
+        assert_eq!(load::<WorldsConfig>("worlds.ron", WORLDS_RON), config);
+        assert_eq!(load::<WorldsConfig>("worlds.toml", WORLDS_TOML), config);
+    }
+
+    #[test]
+    fn toml_dates_are_read_as_text() {
+        let json = r#"{ "actions": [
+            { "date": "2160-01-01", "world": "Earth", "action": "famine", "resource": "carb", "share": 0.5 }
+        ] }"#;
+        let toml = r#"
+            [[actions]]
+            date = 2160-01-01
+            world = "Earth"
+            action = "famine"
+            resource = "carb"
+            share = 0.5
+        "#;
+        assert_eq!(parse("scenario.toml", toml), parse("scenario.json", json));
+        assert_eq!(
+            load::<ScenarioConfig>("scenario.toml", toml),
+            load::<ScenarioConfig>("scenario.json", json)
+        );
+    }
+
+    #[test]
+    fn ron_enum_names_are_lost_without_quotes() {
+        let value = parse("worlds.ron", "(seasons: (hemisphere: Southern))");
+        assert_eq!(value["seasons"]["hemisphere"], Value::Null);
+    }
+
+    #[test]
+    fn parse_errors_have_a_line() {
+        let files = [
+            ("worlds.json", "{\n\"worlds\": [,]}"),
+            ("worlds.ron", "(\nworlds: [,])"),
+            ("worlds.toml", "\nworlds = [,]"),
+        ];
+        for (file, text) in files {
+            let err = ConfigFormat::of_path(file).parse(file, text).unwrap_err();
+            assert_eq!(err.line, Some(2), "{file}: {err}");
+        }
+    }
+}
@@ -0,0 +1,221 @@
+//! Descriptions of what the config files look like, used to build the
+//! config editor without knowing the configs themselves and to write json
+//! schemas of them.
+
+use std::marker::PhantomData;
+
+use chrono::NaiveDate;
+use serde_json::{json, Map, Value};
+
+/// What a value in a config file looks like.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigShape {
+    /// A config, see [`proc_macros::Config`].
+    Struct(Vec<FieldShape>),
+    List(Box<ConfigShape>),
+    /// A value that can be `null`.
+    Optional(Box<ConfigShape>),
+    Pair(Box<ConfigShape>, Box<ConfigShape>),
+    Integer,
+    Number,
+    Bool,
+    Text,
+    /// Text formatted like `2150-01-01`.
+    Date,
+    /// One of the listed texts.
+    Choice(Vec<&'static str>),
+    /// Anything that doesn't implement [`DescribeConfig`].
+    Any,
+}
+
+/// A field of a [`ConfigShape::Struct`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldShape {
+    pub name: &'static str,
+    /// Doc comment of the field.
+    pub description: &'static str,
+    /// Value the field has if it is left out of the file, `None` if it has
+    /// to be in there.
+    pub default: Option<Value>,
+    /// Bounds of `#[range(..)]` and `#[min(..)]`.
+    pub minimum: Option<f64>,
+    pub maximum: Option<f64>,
+    pub shape: ConfigShape,
+}
+
+impl ConfigShape {
+    /// Simplest value of this shape that can be put into a file, only the
+    /// fields without a default are added to structs.
+    pub fn skeleton(&self) -> Value {
+        match self {
+            ConfigShape::Struct(fields) => Value::Object(fields.iter()
+                .filter(|f| f.default.is_none())
+                .map(|f| (f.name.to_string(), f.shape.skeleton()))
+                .collect()),
+            ConfigShape::List(_) => Value::Array(Vec::new()),
+            ConfigShape::Optional(_) | ConfigShape::Any => Value::Null,
+            ConfigShape::Pair(first, second) => Value::Array(vec![
+                first.skeleton(), second.skeleton()
+            ]),
+            ConfigShape::Integer => Value::from(0),
+            ConfigShape::Number => Value::from(0.),
+            ConfigShape::Bool => Value::Bool(false),
+            ConfigShape::Text => Value::from(""),
+            ConfigShape::Date => Value::from(NaiveDate::default().to_string()),
+            ConfigShape::Choice(options) => Value::from(options.first().copied().unwrap_or("")),
+        }
+    }
+
+    /// [JSON Schema](https://json-schema.org) of values of this shape.
+    /// Structs don't allow any fields besides their own, like the loading
+    /// of the configs.
+    pub fn json_schema(&self) -> Value {
+        self.schema(false)
+    }
+
+    /// Same as [`Self::json_schema`], but none of the fields of structs, and
+    /// of the structs nested in them, are required. Meant for parts of a
+    /// config that are completed by something else, like the templates of
+    /// [`crate::worlds::config::WorldsConfig`].
+    pub fn partial_json_schema(&self) -> Value {
+        self.schema(true)
+    }
+
+    fn schema(&self, partial: bool) -> Value {
+        match self {
+            ConfigShape::Struct(fields) => {
+                let mut schema = json!({
+                    "type": "object",
+                    "properties": fields.iter()
+                        .map(|f| (f.name.to_string(), f.json_schema(partial)))
+                        .collect::<Map<_, _>>(),
+                    "additionalProperties": false,
+                });
+                if !partial {
+                    schema["required"] = json!(fields.iter()
+                        .filter(|f| f.default.is_none())
+                        .map(|f| f.name)
+                        .collect::<Vec<_>>());
+                }
+                schema
+            }
+            ConfigShape::List(entries) => json!({
+                "type": "array",
+                "items": entries.json_schema(),
+            }),
+            ConfigShape::Optional(inner) => json!({
+                "anyOf": [inner.schema(partial), { "type": "null" }],
+            }),
+            ConfigShape::Pair(first, second) => json!({
+                "type": "array",
+                "items": [first.json_schema(), second.json_schema()],
+                "minItems": 2,
+                "maxItems": 2,
+            }),
+            ConfigShape::Integer => json!({ "type": "integer" }),
+            ConfigShape::Number => json!({ "type": "number" }),
+            ConfigShape::Bool => json!({ "type": "boolean" }),
+            ConfigShape::Text => json!({ "type": "string" }),
+            ConfigShape::Date => json!({ "type": "string", "format": "date" }),
+            ConfigShape::Choice(options) => json!({ "enum": options }),
+            ConfigShape::Any => json!({}),
+        }
+    }
+}
+
+impl FieldShape {
+    /// Schema of the field's shape, together with its description, default
+    /// and bounds.
+    fn json_schema(&self, partial: bool) -> Value {
+        let mut schema = self.shape.schema(partial);
+        let fields = schema.as_object_mut().unwrap();
+        if !self.description.is_empty() {
+            fields.insert("description".to_string(), Value::from(self.description));
+        }
+        if let Some(default) = &self.default {
+            fields.insert("default".to_string(), default.clone());
+        }
+        if let Some(minimum) = self.minimum {
+            fields.insert("minimum".to_string(), Value::from(minimum));
+        }
+        if let Some(maximum) = self.maximum {
+            fields.insert("maximum".to_string(), Value::from(maximum));
+        }
+        schema
+    }
+}
+
+/// Types that can describe what they look like in a config file.
+/// Implemented by the [`proc_macros::Config`] derive.
+pub trait DescribeConfig {
+    fn shape() -> ConfigShape;
+}
+
+macro_rules! describe_as {
+    ($shape:expr => $($ty:ty),*) => {
+        $(impl DescribeConfig for $ty {
+            fn shape() -> ConfigShape {
+                $shape
+            }
+        })*
+    };
+}
+
+describe_as!(ConfigShape::Integer => u32, u64, usize, i32, i64, isize);
+describe_as!(ConfigShape::Number => f32, f64);
+describe_as!(ConfigShape::Bool => bool);
+describe_as!(ConfigShape::Text => String);
+describe_as!(ConfigShape::Date => NaiveDate);
+
+impl<T: DescribeConfig> DescribeConfig for Option<T> {
+    fn shape() -> ConfigShape {
+        ConfigShape::Optional(Box::new(T::shape()))
+    }
+}
+
+impl<T: DescribeConfig> DescribeConfig for Vec<T> {
+    fn shape() -> ConfigShape {
+        ConfigShape::List(Box::new(T::shape()))
+    }
+}
+
+impl<A: DescribeConfig, B: DescribeConfig> DescribeConfig for (A, B) {
+    fn shape() -> ConfigShape {
+        ConfigShape::Pair(Box::new(A::shape()), Box::new(B::shape()))
+    }
+}
+
+/// Lets the [`proc_macros::Config`] derive describe every field, whether its
+/// type implements [`DescribeConfig`] or not, like
+/// [`super::FieldsOf`] does for [`super::ConfigFields`].
+#[doc(hidden)]
+pub struct ShapeOf<T>(PhantomData<T>);
+
+impl<T> ShapeOf<T> {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+#[doc(hidden)]
+pub trait NestedShape {
+    fn shape(&self) -> ConfigShape;
+}
+
+impl<T: DescribeConfig> NestedShape for ShapeOf<T> {
+    fn shape(&self) -> ConfigShape {
+        T::shape()
+    }
+}
+
+#[doc(hidden)]
+pub trait PlainShape {
+    fn shape(&self) -> ConfigShape;
+}
+
+impl<T> PlainShape for &ShapeOf<T> {
+    fn shape(&self) -> ConfigShape {
+        ConfigShape::Any
+    }
+}
@@ -0,0 +1,407 @@
+//! Deciding when a run is over and reporting on it.
+//!
+//! At the end of every game day the conditions of the `end_conditions`
+//! section of `config.json` are checked, see [`EndConditionsConfig`]. The
+//! first one that is met pauses the simulation, sends a [`SimulationEnded`]
+//! event and writes a [`FinalReport`] with one entry per colony to
+//! `<report_directory>/<config name>_seed<seed>_<date>.json`, followed by the
+//! [`OutputSuffix`] of the run if it has one.
+//!
+//! The report also stays around as a resource, the windowed app shows it in
+//! a summary window and the headless app exits once the event is sent.
+
+use std::{collections::BTreeMap, fmt, fs, io, path::PathBuf};
+
+use bevy::{ecs::event::ManualEventReader, prelude::*};
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use crate::{
+    config::{ConfigOverride, ConfigOverrides, EndConditionsConfig, SelectedConfigPath, ThirdLifeConfig},
+    headless::OutputSuffix,
+    rng::SimRng,
+    time::{DailyTick, GameDate, SimulationSpeed},
+    worlds::{
+        env_and_infra::components::SanitationInfrastructure,
+        food::components::{CarbResource, FoodResource, MeatResource, ResourceOf},
+        init_colonies,
+        population::{
+            components::{Citizen, CitizenOf, Female, Male, Retiree, Starving, Youngling},
+            events::{CitizenCreated, CitizenDied, DeathReason},
+            init_citizens,
+        },
+        snapshot::restore_snapshot,
+        stability::components::Stability,
+        wealth::components::WealthAndSpending,
+        SimulationSet, WorldEntity,
+    },
+    SimulationState,
+};
+
+pub struct EndConditionsPlugin;
+
+impl Plugin for EndConditionsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<SimulationEnded>()
+            .add_systems(
+                OnEnter(SimulationState::Running),
+                init_end_conditions
+                    .after(init_colonies)
+                    .after(init_citizens)
+                    .after(restore_snapshot)
+            )
+            .add_systems(
+                DailyTick,
+                (track_run, check_end_conditions, write_final_report.run_if(on_event::<SimulationEnded>()))
+                    .chain()
+                    .after(SimulationSet::Statistics)
+                    .run_if(not(resource_exists::<FinalReport>))
+            );
+    }
+}
+
+/// The conditions of the current run. Read from the [`ThirdLifeConfig`] but
+/// can be changed before the first day, like the headless app does with the
+/// run length given on the command line.
+#[derive(Resource, Debug, Clone)]
+pub struct EndConditions {
+    pub date: Option<NaiveDate>,
+    pub total_extinction: bool,
+    pub colony_extinction: bool,
+    pub min_population: Option<usize>,
+    pub report_directory: String,
+}
+
+impl From<EndConditionsConfig> for EndConditions {
+    fn from(config: EndConditionsConfig) -> Self {
+        Self {
+            date: config.date(),
+            total_extinction: config.total_extinction(),
+            colony_extinction: config.colony_extinction(),
+            min_population: config.min_population(),
+            report_directory: config.report_directory(),
+        }
+    }
+}
+
+/// Why a run ended.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum EndReason {
+    Date { date: NaiveDate },
+    TotalExtinction,
+    ColonyExtinction { world: String },
+    PopulationBelow { world: String, min_population: usize },
+}
+
+impl fmt::Display for EndReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EndReason::Date { date } => write!(f, "reached {date}"),
+            EndReason::TotalExtinction => write!(f, "every colony died out"),
+            EndReason::ColonyExtinction { world } => write!(f, "{world} died out"),
+            EndReason::PopulationBelow { world, min_population } => {
+                write!(f, "the population of {world} fell below {min_population}")
+            }
+        }
+    }
+}
+
+/// Sent once, on the day the run ended.
+#[derive(Event, Debug, Clone)]
+pub struct SimulationEnded {
+    pub date: NaiveDate,
+    pub reason: EndReason,
+}
+
+/// Totals of the whole run, per colony.
+#[derive(Resource, Default)]
+struct RunTotals {
+    colonies: BTreeMap<Entity, ColonyTotals>,
+    /// Kept here for the same reason as in
+    /// [`crate::metrics::time_series::TimeSeries`].
+    births: ManualEventReader<CitizenCreated>,
+}
+
+#[derive(Default, Clone, Copy, Serialize)]
+pub struct ColonyTotals {
+    pub births: usize,
+    pub deaths_old_age: usize,
+    pub deaths_starvation: usize,
+    pub deaths_infant: usize,
+    pub deaths_plague: usize,
+    /// Stocks on the first day of the run.
+    #[serde(skip)]
+    start_stocks: Option<FoodStocks>,
+}
+
+#[derive(Default, Clone, Copy, Debug, Serialize)]
+pub struct FoodStocks {
+    pub food: f32,
+    pub carb: f32,
+    pub meat: f32,
+}
+
+/// Report on every colony at the end of the run.
+#[derive(Resource, Debug, Clone, Serialize)]
+pub struct FinalReport {
+    pub config: String,
+    pub seed: u64,
+    /// Values that replaced the ones of the config files, see
+    /// [`ConfigOverrides`].
+    pub overrides: Vec<ConfigOverride>,
+    pub date: NaiveDate,
+    pub reason: EndReason,
+    pub colonies: Vec<ColonyReport>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ColonyReport {
+    pub world: String,
+    pub demographics: Demographics,
+    pub food: FoodBalance,
+    pub wealth: WealthReport,
+    pub stability: Stability,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Demographics {
+    pub population: usize,
+    pub men: usize,
+    pub women: usize,
+    pub younglings: usize,
+    pub retirees: usize,
+    /// `null` if nobody is left.
+    pub average_age: f32,
+    /// `null` if there are no women left.
+    #[serde(with = "crate::common::utils::nan_as_null")]
+    pub average_children_per_mother: f32,
+    pub births: usize,
+    pub deaths_old_age: usize,
+    pub deaths_starvation: usize,
+    pub deaths_infant: usize,
+    pub deaths_plague: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FoodBalance {
+    pub start: FoodStocks,
+    pub end: FoodStocks,
+    /// Citizens that are starving on the last day.
+    pub starving: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WealthReport {
+    pub total_wealth: f32,
+    pub spending_available: f32,
+    pub civil_spending: f32,
+    pub sanitation_spending: f32,
+    pub social_spending: f32,
+    pub environmental_spending: f32,
+    pub health_index_score: f32,
+}
+
+pub(crate) fn init_end_conditions(
+    mut commands: Commands,
+    config: Res<ThirdLifeConfig>,
+    births: Res<Events<CitizenCreated>>,
+) {
+    commands.insert_resource(EndConditions::from(config.end_conditions()));
+    commands.insert_resource(RunTotals {
+        births: births.get_reader_current(),
+        ..default()
+    });
+}
+
+fn food_stocks(resources: &Query<FoodStockQuery>) -> BTreeMap<Entity, FoodStocks> {
+    let mut stocks = BTreeMap::<Entity, FoodStocks>::new();
+    for (of, food, carb, meat) in resources {
+        let stock = stocks.entry(of.colony).or_default();
+        stock.food += food.map_or(0., |r| r.amount);
+        stock.carb += carb.map_or(0., |r| r.amount);
+        stock.meat += meat.map_or(0., |r| r.amount);
+    }
+    stocks
+}
+
+type FoodStockQuery<'a> = (
+    &'a ResourceOf, Option<&'a FoodResource>, Option<&'a CarbResource>, Option<&'a MeatResource>
+);
+
+fn track_run(
+    mut totals: ResMut<RunTotals>,
+    created: Res<Events<CitizenCreated>>,
+    mut deaths: EventReader<CitizenDied>,
+    colonies: Query<Entity, With<WorldEntity>>,
+    resources: Query<FoodStockQuery>,
+) {
+    let totals = totals.as_mut();
+    for created in totals.births.read(&created).filter(|c| c.age == 0) {
+        totals.colonies.entry(created.colony).or_default().births += 1;
+    }
+    for died in deaths.read() {
+        let colony = totals.colonies.entry(died.colony).or_default();
+        match died.reason {
+            DeathReason::OldAge => colony.deaths_old_age += 1,
+            DeathReason::Starvation => colony.deaths_starvation += 1,
+            DeathReason::InfantDeath => colony.deaths_infant += 1,
+            DeathReason::Plague => colony.deaths_plague += 1,
+        }
+    }
+    let stocks = food_stocks(&resources);
+    for colony in &colonies {
+        let colony_totals = totals.colonies.entry(colony).or_default();
+        if colony_totals.start_stocks.is_none() {
+            colony_totals.start_stocks = Some(stocks.get(&colony).copied().unwrap_or_default());
+        }
+    }
+}
+
+fn check_end_conditions(
+    conditions: Res<EndConditions>,
+    game_date: Res<GameDate>,
+    mut speed: ResMut<SimulationSpeed>,
+    mut ended: EventWriter<SimulationEnded>,
+    colonies: Query<(Entity, &WorldEntity)>,
+    citizens: Query<&CitizenOf>,
+) {
+    let mut populations = colonies.iter()
+        .map(|(colony, world)| (colony, (world.name(), 0)))
+        .collect::<BTreeMap<_, _>>();
+    for of in &citizens {
+        if let Some((_, population)) = populations.get_mut(&of.colony) {
+            *population += 1;
+        }
+    }
+
+    let empty = populations.values().find(|(_, population)| *population == 0);
+    let below = conditions.min_population.and_then(|min| populations.values()
+        .find(|(_, population)| *population < min)
+        .map(|(world, _)| (world, min))
+    );
+    let reason = if conditions.total_extinction && populations.values().all(|(_, p)| *p == 0) {
+        Some(EndReason::TotalExtinction)
+    } else if let Some((world, _)) = empty.filter(|_| conditions.colony_extinction) {
+        Some(EndReason::ColonyExtinction { world: world.to_string() })
+    } else if let Some((world, min_population)) = below {
+        Some(EndReason::PopulationBelow { world: world.to_string(), min_population })
+    } else {
+        conditions.date
+            .filter(|date| game_date.date >= *date)
+            .map(|date| EndReason::Date { date })
+    };
+
+    if let Some(reason) = reason {
+        info!("The simulation ended on {}: {reason}", game_date.date);
+        *speed = SimulationSpeed::Paused;
+        ended.send(SimulationEnded { date: game_date.date, reason });
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn write_final_report(
+    mut commands: Commands,
+    mut ended: EventReader<SimulationEnded>,
+    conditions: Res<EndConditions>,
+    config_path: Res<SelectedConfigPath>,
+    overrides: Res<ConfigOverrides>,
+    sim_rng: Res<SimRng>,
+    suffix: Option<Res<OutputSuffix>>,
+    totals: Res<RunTotals>,
+    game_date: Res<GameDate>,
+    colonies: Query<ReportedColony>,
+    citizens: Query<CitizenQuery>,
+    resources: Query<FoodStockQuery>,
+) {
+    let Some(SimulationEnded { date, reason }) = ended.read().last().cloned() else {
+        return;
+    };
+    let stocks = food_stocks(&resources);
+    let colonies = colonies.iter()
+        .map(|(colony, world, wealth, sanitation, stability)| {
+            let citizens = citizens.iter().filter(|c| c.1.colony == colony).collect::<Vec<_>>();
+            let totals = totals.colonies.get(&colony).copied().unwrap_or_default();
+            let women = citizens.iter().filter_map(|c| c.3).collect::<Vec<_>>();
+            let ages = citizens.iter()
+                .map(|c| game_date.years_since(c.0.birthday).unwrap_or(0) as f32)
+                .sum::<f32>();
+            ColonyReport {
+                world: world.name().to_string(),
+                demographics: Demographics {
+                    population: citizens.len(),
+                    men: citizens.iter().filter(|c| c.2).count(),
+                    women: women.len(),
+                    younglings: citizens.iter().filter(|c| c.4).count(),
+                    retirees: citizens.iter().filter(|c| c.5).count(),
+                    average_age: ages / citizens.len() as f32,
+                    average_children_per_mother: women.iter()
+                        .map(|w| w.children_had as f32)
+                        .sum::<f32>() / women.len() as f32,
+                    births: totals.births,
+                    deaths_old_age: totals.deaths_old_age,
+                    deaths_starvation: totals.deaths_starvation,
+                    deaths_infant: totals.deaths_infant,
+                    deaths_plague: totals.deaths_plague,
+                },
+                food: FoodBalance {
+                    start: totals.start_stocks.unwrap_or_default(),
+                    end: stocks.get(&colony).copied().unwrap_or_default(),
+                    starving: citizens.iter().filter(|c| c.6).count(),
+                },
+                wealth: WealthReport {
+                    total_wealth: wealth.total_wealth,
+                    spending_available: wealth.spending_available,
+                    civil_spending: wealth.total_civil_spending(),
+                    sanitation_spending: wealth.total_sanitation_spending(),
+                    social_spending: wealth.total_social_spending(),
+                    environmental_spending: wealth.total_environmental_spending(),
+                    health_index_score: sanitation.health_index_score,
+                },
+                stability: *stability,
+            }
+        })
+        .collect();
+
+    let report = FinalReport {
+        config: config_path.name().to_string(),
+        seed: sim_rng.seed(),
+        overrides: overrides.0.clone(),
+        date,
+        reason,
+        colonies,
+    };
+    let path = PathBuf::from(&conditions.report_directory)
+        .join(format!("{}_seed{}_{}.json", report.config, report.seed, report.date));
+    let path = OutputSuffix::apply(suffix.as_deref(), path);
+    match report.write(&path) {
+        Ok(()) => info!("Wrote the final report to {}", path.display()),
+        Err(err) => error!("Could not write the final report to {}: {err}", path.display()),
+    }
+    commands.insert_resource(report);
+}
+
+type ReportedColony<'a> = (
+    Entity, &'a WorldEntity, &'a WealthAndSpending, &'a SanitationInfrastructure, &'a Stability
+);
+
+type CitizenQuery<'a> = (
+    &'a Citizen,
+    &'a CitizenOf,
+    Has<Male>,
+    Option<&'a Female>,
+    Has<Youngling>,
+    Has<Retiree>,
+    Has<Starving>,
+);
+
+impl FinalReport {
+    fn write(&self, path: &PathBuf) -> io::Result<()> {
+        if let Some(folder) = path.parent() {
+            fs::create_dir_all(folder)?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(path, json)
+    }
+}
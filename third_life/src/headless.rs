@@ -0,0 +1,238 @@
+//! Running the simulation without a window.
+//!
+//! Meant for long experiments on machines without a display. Instead of the
+//! `DefaultPlugins` only the [`MinimalPlugins`] and the [`AssetPlugin`] are
+//! used, the config is provided up front and the app exits on its own once
+//! the requested amount of game time has been simulated or any other of the
+//! [`EndConditions`] was met.
+//!
+//! The config loading works exactly like in the windowed app, the only
+//! difference is that [`SelectedConfigPath`] is inserted before the
+//! selection window would be shown, see [`crate::config`]. Since there is no
+//! other config to pick, mistakes in it end the run right away, see
+//! [`ConfigFailed`].
+
+use std::{
+    path::PathBuf,
+    sync::{atomic::{AtomicBool, Ordering}, Arc},
+};
+
+use bevy::{app::AppExit, prelude::*};
+use chrono::{Days, Months, NaiveDate};
+use clap::{Args, Parser};
+
+use crate::{
+    cli::SimulationArgs,
+    config::{error::ConfigErrors, ConfigurationPlugin, SelectedConfigPath},
+    end_conditions::{init_end_conditions, EndConditions, EndConditionsPlugin, SimulationEnded},
+    metrics::MetricsExportPlugin,
+    time::{init_start_date, GameDate, SimulationSpeed, TimeDatePlugin},
+    worlds::{snapshot::{restore_snapshot, save_snapshot, SaveSnapshot}, WorldsPlugin},
+    SimulationState,
+};
+
+/// Command line arguments of the headless entry point.
+#[derive(Parser, Debug, Clone)]
+#[command(about = "Runs the Third Life simulation without a window")]
+pub struct HeadlessArgs {
+    /// Name of the config folder inside of `assets/config`
+    #[arg(long, required_unless_present = "snapshot", conflicts_with = "snapshot")]
+    pub config: Option<String>,
+    #[command(flatten)]
+    pub run_length: RunLengthArgs,
+    /// Saves a snapshot of the final state to the given file
+    #[arg(long)]
+    pub save_snapshot: Option<PathBuf>,
+    #[command(flatten)]
+    pub simulation: SimulationArgs,
+}
+
+impl HeadlessArgs {
+    pub fn run_length(&self) -> RunLength {
+        self.run_length.run_length()
+    }
+}
+
+/// Command line arguments for the [`RunLength`], shared with the batch
+/// runner.
+#[derive(Args, Debug, Clone)]
+pub struct RunLengthArgs {
+    /// Amount of game days to simulate
+    #[arg(long, required_unless_present = "years", conflicts_with = "years")]
+    pub days: Option<u64>,
+    /// Amount of game years to simulate
+    #[arg(long)]
+    pub years: Option<u32>,
+}
+
+impl RunLengthArgs {
+    pub fn run_length(&self) -> RunLength {
+        match (self.days, self.years) {
+            (Some(days), _) => RunLength::Days(days),
+            (None, Some(years)) => RunLength::Years(years),
+            (None, None) => unreachable!("clap requires either days or years"),
+        }
+    }
+}
+
+/// For how long the simulation should run before exiting.
+#[derive(Resource, Debug, Clone, Copy)]
+pub enum RunLength {
+    Days(u64),
+    Years(u32),
+}
+
+impl RunLength {
+    fn end_date(&self, start: NaiveDate) -> NaiveDate {
+        match self {
+            RunLength::Days(days) => start + Days::new(*days),
+            RunLength::Years(years) => start + Months::new(years * 12),
+        }
+    }
+}
+
+/// File the final state is saved to once the run ends.
+#[derive(Resource, Debug)]
+pub struct SnapshotOnEnd(pub PathBuf);
+
+/// Added to the names of the files a run writes, so that runs of the same
+/// config and seed don't overwrite each other's output. The batch runner
+/// sets it to the swept values of a run.
+#[derive(Resource, Debug, Clone)]
+pub struct OutputSuffix(pub String);
+
+impl OutputSuffix {
+    /// `path` with the suffix added to its file name, in front of the
+    /// extension.
+    pub fn apply(suffix: Option<&Self>, path: PathBuf) -> PathBuf {
+        let Some(OutputSuffix(suffix)) = suffix else {
+            return path;
+        };
+        let mut name = path.file_stem().unwrap_or_default().to_os_string();
+        name.push(format!("_{suffix}"));
+        if let Some(extension) = path.extension() {
+            name.push(".");
+            name.push(extension);
+        }
+        path.with_file_name(name)
+    }
+}
+
+/// Set once the run was ended because the config could not be loaded. The
+/// flag is shared, so it can be cloned out of the app before [`App::run`]
+/// and checked once it returned.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct ConfigFailed(Arc<AtomicBool>);
+
+impl ConfigFailed {
+    pub fn is_set(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Adds everything that is needed to run the simulation without a window.
+///
+/// Without a window there is no reason to wait for real time to pass, so a
+/// new day starts on every update. Only a single day is simulated per update
+/// so that the run stops exactly on the day a [`SimulationEnded`] is sent.
+pub struct HeadlessPlugin {
+    /// Can be left out if the [`SelectedConfigPath`] is provided otherwise,
+    /// for example by a snapshot.
+    pub config: Option<String>,
+    pub run_length: RunLength,
+}
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        if let Some(config) = &self.config {
+            app.insert_resource(SelectedConfigPath::new_std(config.clone()));
+        }
+        app
+            .insert_resource(self.run_length)
+            .insert_resource(SimulationSpeed::DaysPerFrame(1))
+            .init_resource::<ConfigFailed>()
+            .add_systems(
+                OnEnter(SimulationState::Running),
+                init_run_end
+                    .after(init_start_date)
+                    .after(restore_snapshot)
+                    .after(init_end_conditions)
+            )
+            .add_systems(
+                Update,
+                exit_on_config_errors.run_if(in_state(SimulationState::ConfigSelection))
+            )
+            .add_systems(
+                Update,
+                exit_on_end
+                    .before(save_snapshot)
+                    .run_if(in_state(SimulationState::Running))
+            );
+    }
+}
+
+/// Creates the headless app with all of the simulation plugins. Logging is
+/// left to the caller since only one logger can exist per process.
+pub fn build_app(config: Option<String>, run_length: RunLength) -> App {
+    let mut app = App::new();
+    app
+        .init_state::<SimulationState>()
+        .add_plugins((
+            MinimalPlugins,
+            // INFO: Nobody is around to change the config files during a
+            // headless run, and batches would start a watcher per run.
+            AssetPlugin { watch_for_changes_override: Some(false), ..default() },
+            ConfigurationPlugin,
+            TimeDatePlugin,
+            WorldsPlugin,
+            MetricsExportPlugin,
+            EndConditionsPlugin,
+            HeadlessPlugin { config, run_length },
+        ));
+    app
+}
+
+/// Ends the run once the config could not be loaded, since there is nobody
+/// to pick another one. The [`ConfigErrors`] themselves were already logged
+/// while loading. The batch runner reports them for the run, the headless
+/// binary exits with an error code.
+fn exit_on_config_errors(
+    errors: Res<ConfigErrors>,
+    failed: Res<ConfigFailed>,
+    mut exit: EventWriter<AppExit>,
+) {
+    if errors.0.is_empty() || failed.is_set() {
+        return;
+    }
+    error!("The config could not be loaded, ending the run");
+    failed.0.store(true, Ordering::Relaxed);
+    exit.send(AppExit);
+}
+
+/// The run length ends the run unless the end date of the config comes
+/// first.
+fn init_run_end(
+    run_length: Res<RunLength>,
+    game_date: Res<GameDate>,
+    mut conditions: ResMut<EndConditions>,
+) {
+    let end = run_length.end_date(game_date.date);
+    let end = conditions.date.map_or(end, |date| date.min(end));
+    info!("Running headless from {} until {} at the latest", game_date.date, end);
+    conditions.date = Some(end);
+}
+
+fn exit_on_end(
+    mut ended: EventReader<SimulationEnded>,
+    snapshot_on_end: Option<Res<SnapshotOnEnd>>,
+    mut save: EventWriter<SaveSnapshot>,
+    mut exit: EventWriter<AppExit>,
+) {
+    if let Some(ended) = ended.read().last() {
+        info!("Ending the headless run on {}: {}", ended.date, ended.reason);
+        if let Some(SnapshotOnEnd(path)) = snapshot_on_end.as_deref() {
+            save.send(SaveSnapshot { path: path.clone() });
+        }
+        exit.send(AppExit);
+    }
+}
@@ -0,0 +1,39 @@
+//! Simulation of worlds and their inhabitatnts. Models a number of things
+//! which have been researched but still dumbed down to make them easier to
+//! implement. For more details check out the [Gitlab Wiki](https://gitlab.com/groups/kdg-ti/the-lab/teams-23-24/third-life/-/wikis/home)
+//!
+//! The simulation itself lives in this library so that it can be started
+//! from multiple entry points. The windowed app is in `main.rs` and the
+//! headless one is in `bin/headless.rs`, see [`headless`] for more. Many
+//! headless runs can be compared with `bin/batch.rs`, see [`batch`].
+
+mod common;
+/// Coponets for the reading and creation of config Files
+pub mod config;
+pub mod time;
+pub mod worlds;
+pub mod animation;
+pub mod ui;
+pub mod headless;
+pub mod rng;
+pub mod cli;
+pub mod metrics;
+pub mod batch;
+pub mod end_conditions;
+
+use bevy::prelude::*;
+
+/// State of the simulation, manages the initial configuration load
+/// menu.
+///
+/// Has three states in which the configuration can be selected the
+/// configuration is loading and then has finished loading. The intermediary
+/// step is important becase initializasions needs the configuration to be
+/// completly loaded.
+#[derive(Debug, Clone, States, Default, PartialEq, PartialOrd, Ord, Eq, Hash)]
+pub enum SimulationState {
+    #[default]
+    ConfigSelection,
+    LoadingConfig,
+    Running,
+}
@@ -1,44 +1,31 @@
-//! Simulation of worlds and their inhabitatnts. Models a number of things
-//! which have been researched but still dumbed down to make them easier to
-//! implement. For more details check out the [Gitlab Wiki](https://gitlab.com/groups/kdg-ti/the-lab/teams-23-24/third-life/-/wikis/home)
+use std::process::exit;
 
-mod common;
-/// Coponets for the reading and creation of config Files
-mod config;
-mod time;
-mod worlds;
-mod animation;
-mod ui;
-
-
-
-use animation::ThirdLifeAnimationPlugin;
 use bevy::{log::LogPlugin, prelude::*};
 use bevy_egui::{
     EguiPlugin,
 };
-use config::ConfigurationPlugin;
-use time::TimeDatePlugin;
-use ui::ThridLifeUiPlugin;
-use worlds::WorldsPlugin;
+use clap::Parser;
+use third_life::{
+    animation::ThirdLifeAnimationPlugin, cli::SimulationArgs, config::ConfigurationPlugin,
+    end_conditions::EndConditionsPlugin, metrics::MetricsExportPlugin, time::TimeDatePlugin, ui::ThridLifeUiPlugin,
+    worlds::WorldsPlugin, SimulationState,
+};
 
-/// State of the simulation, manages the initial configuration load
-/// menu.
-///
-/// Has three states in which the configuration can be selected the
-/// configuration is loading and then has finished loading. The intermediary
-/// step is important becase initializasions needs the configuration to be
-/// completly loaded.
-#[derive(Debug, Clone, States, Default, PartialEq, PartialOrd, Ord, Eq, Hash)]
-pub enum SimulationState {
-    #[default]
-    ConfigSelection,
-    LoadingConfig,
-    Running,
+#[derive(Parser, Debug)]
+#[command(about = "Runs the Third Life simulation")]
+struct Args {
+    #[command(flatten)]
+    simulation: SimulationArgs,
 }
 
 fn main() {
-    App::new()
+    let args = Args::parse();
+    let mut app = App::new();
+    if let Err(err) = args.simulation.insert_resources(&mut app) {
+        eprintln!("{err}");
+        exit(1);
+    }
+    app
         .init_state::<SimulationState>()
         .add_plugins((
             DefaultPlugins
@@ -59,6 +46,8 @@ fn main() {
             ConfigurationPlugin,
             TimeDatePlugin,
             WorldsPlugin,
+            MetricsExportPlugin,
+            EndConditionsPlugin,
             ThridLifeUiPlugin,
             ThirdLifeAnimationPlugin
         ))
@@ -0,0 +1,221 @@
+//! Export of the statistics of a run to external tools.
+//!
+//! Once per game day a [`DataPoint`] is collected for the resources and the
+//! population of every colony and handed to the configured [`MetricsBackend`].
+//! Which backend is used, and where it writes to, comes from the `metrics`
+//! section of `config.json`, see [`MetricsConfig`]. Nothing is exported if
+//! that section is missing.
+//!
+//! Independent of the backend, [`time_series`] can write a flat CSV file with
+//! the daily statistics of every colony.
+//!
+//! Adding a new backend means implementing [`MetricsBackend`] and adding a
+//! variant to [`MetricsBackendKind`].
+
+pub(crate) mod csv;
+mod http;
+mod line_protocol;
+pub mod time_series;
+
+use std::{collections::HashMap, env, io, path::PathBuf};
+
+use bevy::{app::AppExit, prelude::*};
+use chrono::NaiveDate;
+
+use crate::{
+    config::{MetricsBackendKind, MetricsConfig, ThirdLifeConfig},
+    headless::OutputSuffix,
+    time::{DailyTick, GameDate},
+    worlds::{
+        food::components::{CarbResource, FoodResource, MeatResource, ResourceOf},
+        population::components::Population,
+        stability::components::Stability,
+        SimulationSet, WorldEntity,
+    },
+    SimulationState,
+};
+
+use self::{
+    csv::CsvBackend, http::HttpBackend, line_protocol::LineProtocolFileBackend,
+    time_series::TimeSeriesPlugin,
+};
+
+pub struct MetricsExportPlugin;
+
+impl Plugin for MetricsExportPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(OnEnter(SimulationState::Running), init_metrics)
+            .add_systems(
+                DailyTick,
+                export_day
+                    .after(SimulationSet::Statistics)
+                    .run_if(resource_exists::<Metrics>)
+            )
+            .add_systems(Last, flush_on_exit.run_if(resource_exists::<Metrics>))
+            .add_plugins(TimeSeriesPlugin);
+    }
+}
+
+/// Something the collected statistics can be written to.
+pub trait MetricsBackend: Send + Sync {
+    /// Receives all points of a single game day. Backends are free to buffer
+    /// them until [`Self::flush`] is called.
+    fn write(&mut self, points: &[DataPoint]) -> io::Result<()>;
+    /// Makes sure everything written so far has reached its destination.
+    fn flush(&mut self) -> io::Result<()>;
+}
+
+/// A single measurement of a game day.
+#[derive(Debug, Clone)]
+pub struct DataPoint {
+    pub measurement: &'static str,
+    pub date: NaiveDate,
+    pub tags: Vec<(&'static str, String)>,
+    pub fields: Vec<(&'static str, FieldValue)>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum FieldValue {
+    Float(f64),
+    Integer(i64),
+}
+
+/// The backend of the current run.
+#[derive(Resource)]
+pub struct Metrics {
+    backend: Box<dyn MetricsBackend>,
+}
+
+impl Metrics {
+    pub fn new(backend: Box<dyn MetricsBackend>) -> Self {
+        Self { backend }
+    }
+}
+
+/// Value of the given environment variable, or the one from the config if it
+/// isn't set.
+fn from_env_or(var: &str, config: Option<String>) -> Option<String> {
+    env::var(var).ok().or(config)
+}
+
+fn create_backend(
+    config: &MetricsConfig, suffix: Option<&OutputSuffix>
+) -> io::Result<Box<dyn MetricsBackend>> {
+    let path = OutputSuffix::apply(suffix, PathBuf::from(config.path()));
+    Ok(match config.backend() {
+        MetricsBackendKind::LineProtocolFile => Box::new(LineProtocolFileBackend::create(path)?),
+        MetricsBackendKind::Csv => Box::new(CsvBackend::create(path)?),
+        MetricsBackendKind::Http => {
+            let endpoint = from_env_or("THIRD_LIFE_METRICS_ENDPOINT", config.endpoint());
+            let bucket = from_env_or("THIRD_LIFE_METRICS_BUCKET", config.bucket());
+            let org = from_env_or("THIRD_LIFE_METRICS_ORG", config.org());
+            let token = from_env_or("THIRD_LIFE_METRICS_TOKEN", config.token());
+            let (Some(endpoint), Some(bucket)) = (endpoint, bucket) else {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "the `http` backend needs an `endpoint` and a `bucket`, either in the \
+                     `metrics` section of `config.json` or through the \
+                     `THIRD_LIFE_METRICS_ENDPOINT` and `THIRD_LIFE_METRICS_BUCKET` \
+                     environment variables",
+                ));
+            };
+            Box::new(HttpBackend::new(&endpoint, bucket, org, token)?)
+        }
+    })
+}
+
+fn init_metrics(
+    mut commands: Commands,
+    config: Res<ThirdLifeConfig>,
+    suffix: Option<Res<OutputSuffix>>,
+) {
+    let Some(metrics_config) = config.metrics() else {
+        return;
+    };
+    match create_backend(&metrics_config, suffix.as_deref()) {
+        Ok(backend) => {
+            info!("Exporting metrics through the {:?} backend", metrics_config.backend());
+            commands.insert_resource(Metrics::new(backend));
+        }
+        Err(err) => error!("Could not create the metrics backend, nothing is exported: {err}"),
+    }
+}
+
+fn export_day(
+    mut metrics: ResMut<Metrics>,
+    game_date: Res<GameDate>,
+    colonies: Query<(Entity, &WorldEntity, &Population, &Stability)>,
+    food: Query<(&FoodResource, &ResourceOf)>,
+    carb: Query<(&CarbResource, &ResourceOf)>,
+    meat: Query<(&MeatResource, &ResourceOf)>,
+) {
+    let date = game_date.date;
+    let food = food.iter().map(|(r, of)| (of.colony, r.amount)).collect::<HashMap<_, _>>();
+    let carb = carb.iter().map(|(r, of)| (of.colony, r.amount)).collect::<HashMap<_, _>>();
+    let meat = meat.iter().map(|(r, of)| (of.colony, r.amount)).collect::<HashMap<_, _>>();
+    let amount_of = |amounts: &HashMap<Entity, f32>, colony| {
+        FieldValue::Float(amounts.get(&colony).copied().unwrap_or(0.) as f64)
+    };
+
+    let mut points = Vec::new();
+    for (colony, world, population, stability) in &colonies {
+        let tags = vec![("world", world.name().to_string())];
+
+        points.push(DataPoint {
+            measurement: "resources",
+            date,
+            tags: tags.clone(),
+            fields: vec![
+                ("carb", amount_of(&carb, colony)),
+                ("meat", amount_of(&meat, colony)),
+                ("food", amount_of(&food, colony)),
+            ],
+        });
+        // INFO: Missing estimates are `NaN` so every point has the same fields.
+        let days = |days: Option<u32>| FieldValue::Float(days.map_or(f64::NAN, f64::from));
+        points.push(DataPoint {
+            measurement: "stability",
+            date,
+            tags: vec![
+                ("world", world.name().to_string()),
+                ("label", stability.label.to_string()),
+            ],
+            fields: vec![
+                ("growth", FieldValue::Float(stability.growth)),
+                ("death_rate", FieldValue::Float(stability.death_rate)),
+                ("days_until_starvation", days(stability.days_until_starvation)),
+                ("days_to_collapse", days(stability.days_to_collapse)),
+            ],
+        });
+        points.push(DataPoint {
+            measurement: "population",
+            date,
+            tags,
+            fields: vec![
+                ("population_count", FieldValue::Integer(population.count as i64)),
+                ("working_pop", FieldValue::Integer(population.working_pop as i64)),
+                ("younglings", FieldValue::Integer(population.younglings as i64)),
+                ("retirees", FieldValue::Integer(population.retirees as i64)),
+                ("average_age", FieldValue::Float(population.average_age as f64)),
+                (
+                    "average_children_per_mother",
+                    FieldValue::Float(population.average_children_per_mother as f64)
+                ),
+            ],
+        });
+    }
+
+    if let Err(err) = metrics.backend.write(&points) {
+        error!("Exporting the metrics of {date} failed: {err}");
+    }
+}
+
+fn flush_on_exit(mut metrics: ResMut<Metrics>, exit: EventReader<AppExit>) {
+    if exit.is_empty() {
+        return;
+    }
+    if let Err(err) = metrics.backend.flush() {
+        error!("Flushing the metrics failed: {err}");
+    }
+}
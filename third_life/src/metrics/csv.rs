@@ -0,0 +1,65 @@
+//! Writes every measurement to its own CSV file in a folder, with one column
+//! per tag and field.
+
+use std::{collections::HashMap, fs::{self, File}, io::{self, BufWriter, Write}, path::PathBuf};
+
+use super::{DataPoint, FieldValue, MetricsBackend};
+
+pub struct CsvBackend {
+    folder: PathBuf,
+    files: HashMap<&'static str, BufWriter<File>>,
+}
+
+impl CsvBackend {
+    pub fn create(folder: PathBuf) -> io::Result<Self> {
+        fs::create_dir_all(&folder)?;
+        Ok(Self { folder, files: HashMap::new() })
+    }
+
+    /// File of the measurement, created with a header taken from the first
+    /// point of it.
+    fn file_for(&mut self, point: &DataPoint) -> io::Result<&mut BufWriter<File>> {
+        if !self.files.contains_key(point.measurement) {
+            let path = self.folder.join(format!("{}.csv", point.measurement));
+            let mut writer = BufWriter::new(File::create(path)?);
+            let header = ["date"].into_iter()
+                .chain(point.tags.iter().map(|(key, _)| *key))
+                .chain(point.fields.iter().map(|(key, _)| *key))
+                .map(quote)
+                .collect::<Vec<_>>();
+            writeln!(writer, "{}", header.join(","))?;
+            self.files.insert(point.measurement, writer);
+        }
+        Ok(self.files.get_mut(point.measurement).unwrap())
+    }
+}
+
+impl MetricsBackend for CsvBackend {
+    fn write(&mut self, points: &[DataPoint]) -> io::Result<()> {
+        for point in points {
+            let row = [point.date.to_string()].into_iter()
+                .chain(point.tags.iter().map(|(_, value)| quote(value)))
+                .chain(point.fields.iter().map(|(_, value)| match value {
+                    FieldValue::Float(v) if v.is_nan() => String::new(),
+                    FieldValue::Float(v) => v.to_string(),
+                    FieldValue::Integer(v) => v.to_string(),
+                }))
+                .collect::<Vec<_>>();
+            writeln!(self.file_for(point)?, "{}", row.join(","))?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.files.values_mut().try_for_each(|f| f.flush())
+    }
+}
+
+/// Quotes a value if it contains anything that would break the row.
+pub(crate) fn quote(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -0,0 +1,184 @@
+//! Sends line protocol to the write endpoint of an InfluxDB server
+//! (`/api/v2/write`).
+//!
+//! Only plain `http` is supported, which is enough for a server on the same
+//! machine or network. Points are buffered and sent in batches so a fast
+//! forwarded run doesn't make a request for every single day.
+
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::TcpStream,
+    time::Duration,
+};
+
+use super::{line_protocol::to_line, DataPoint, MetricsBackend};
+
+/// Amount of lines after which the buffer is sent.
+const BATCH_SIZE: usize = 5000;
+
+pub struct HttpBackend {
+    /// `host:port` to connect to.
+    address: String,
+    host: String,
+    /// Path of the write endpoint including the query.
+    path: String,
+    token: Option<String>,
+    lines: Vec<String>,
+}
+
+impl HttpBackend {
+    /// Fails if the endpoint isn't a plain `http://` url.
+    pub fn new(endpoint: &str, bucket: String, org: Option<String>, token: Option<String>) -> io::Result<Self> {
+        let Some(rest) = endpoint.strip_prefix("http://") else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("the metrics endpoint {endpoint} is not supported, only plain `http://` endpoints can be used"),
+            ));
+        };
+        let (host, base_path) = match rest.split_once('/') {
+            Some((host, path)) => (host, format!("/{}", path.trim_end_matches('/'))),
+            None => (rest, String::new()),
+        };
+        let address = if host.contains(':') { host.to_string() } else { format!("{host}:80") };
+
+        let mut path = format!("{base_path}/api/v2/write?bucket={}&precision=s", encode(&bucket));
+        if let Some(org) = org {
+            path.push_str(&format!("&org={}", encode(&org)));
+        }
+
+        Ok(Self { address, host: host.to_string(), path, token, lines: Vec::new() })
+    }
+
+    fn send(&self, body: &str) -> io::Result<()> {
+        let mut stream = TcpStream::connect(&self.address)?;
+        stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+
+        let mut request = format!(
+            "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n",
+            self.path, self.host, body.len()
+        );
+        if let Some(token) = &self.token {
+            request.push_str(&format!("Authorization: Token {token}\r\n"));
+        }
+        request.push_str("\r\n");
+        stream.write_all(request.as_bytes())?;
+        stream.write_all(body.as_bytes())?;
+
+        let mut status = String::new();
+        BufReader::new(stream).read_line(&mut status)?;
+        match status.split_whitespace().nth(1) {
+            Some(code) if code.starts_with('2') => Ok(()),
+            _ => Err(io::Error::other(format!("the server answered with `{}`", status.trim()))),
+        }
+    }
+}
+
+impl MetricsBackend for HttpBackend {
+    fn write(&mut self, points: &[DataPoint]) -> io::Result<()> {
+        self.lines.extend(points.iter().filter_map(to_line));
+        if self.lines.len() >= BATCH_SIZE {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.lines.is_empty() {
+            return Ok(());
+        }
+        let body = self.lines.join("\n");
+        // INFO: The lines are dropped even if sending failed, otherwise a
+        // server that is down would make the buffer grow forever.
+        self.lines.clear();
+        self.send(&body)
+    }
+}
+
+/// Percent encodes everything except for unreserved characters.
+fn encode(str: &str) -> String {
+    str.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Read, net::TcpListener, thread};
+
+    use chrono::NaiveDate;
+
+    use super::*;
+    use crate::metrics::FieldValue;
+
+    /// Answers a single request with `204 No Content` and returns it.
+    fn serve_once(listener: TcpListener) -> thread::JoinHandle<String> {
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0; 1024];
+            // INFO: Reads until the whole body announced in the header arrived.
+            loop {
+                let read = stream.read(&mut buf).unwrap();
+                request.extend_from_slice(&buf[..read]);
+                let text = String::from_utf8_lossy(&request);
+                if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                    let length = head.lines()
+                        .find_map(|l| l.strip_prefix("Content-Length: "))
+                        .map_or(0, |l| l.parse().unwrap());
+                    if body.len() >= length {
+                        break;
+                    }
+                }
+                if read == 0 {
+                    break;
+                }
+            }
+            stream.write_all(b"HTTP/1.1 204 No Content\r\n\r\n").unwrap();
+            String::from_utf8(request).unwrap()
+        })
+    }
+
+    #[test]
+    fn posts_batch_to_write_endpoint() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let server = serve_once(listener);
+
+        let mut backend = HttpBackend::new(
+            &endpoint,
+            "third life".to_string(),
+            Some("lab".to_string()),
+            Some("secret".to_string()),
+        ).unwrap();
+        let point = DataPoint {
+            measurement: "population",
+            date: NaiveDate::from_ymd_opt(2150, 1, 1).unwrap(),
+            tags: vec![("world", "Earth".to_string())],
+            fields: vec![("count", FieldValue::Integer(1000))],
+        };
+        backend.write(&[point.clone(), point]).unwrap();
+        backend.flush().unwrap();
+
+        let request = server.join().unwrap();
+        let (head, body) = request.split_once("\r\n\r\n").unwrap();
+        let mut lines = head.lines();
+        assert_eq!(
+            lines.next(),
+            Some("POST /api/v2/write?bucket=third%20life&precision=s&org=lab HTTP/1.1")
+        );
+        assert!(lines.any(|l| l == "Authorization: Token secret"));
+        let line = "population,world=Earth count=1000i 5680281600";
+        assert_eq!(body, format!("{line}\n{line}"));
+    }
+
+    #[test]
+    fn rejects_https_endpoints() {
+        let backend = HttpBackend::new("https://localhost:8086", "bucket".to_string(), None, None);
+        assert_eq!(backend.err().map(|e| e.kind()), Some(io::ErrorKind::InvalidInput));
+    }
+}
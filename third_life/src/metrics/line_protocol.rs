@@ -0,0 +1,66 @@
+//! InfluxDB line protocol, written to a local file.
+//!
+//! Timestamps are in seconds, so the file has to be imported with a precision
+//! of seconds, for example `influx write --precision s --file metrics.lp`.
+
+use std::{fs::{self, File}, io::{self, BufWriter, Write}, path::PathBuf};
+
+use chrono::NaiveTime;
+
+use super::{DataPoint, FieldValue, MetricsBackend};
+
+pub struct LineProtocolFileBackend {
+    writer: BufWriter<File>,
+}
+
+impl LineProtocolFileBackend {
+    pub fn create(path: PathBuf) -> io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        Ok(Self { writer: BufWriter::new(File::create(path)?) })
+    }
+}
+
+impl MetricsBackend for LineProtocolFileBackend {
+    fn write(&mut self, points: &[DataPoint]) -> io::Result<()> {
+        for line in points.iter().filter_map(to_line) {
+            writeln!(self.writer, "{line}")?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Formats a point as a single line, `None` if it has no field with a value.
+/// InfluxDB has no `NaN` so those fields are left out.
+pub(super) fn to_line(point: &DataPoint) -> Option<String> {
+    let fields = point.fields.iter()
+        .filter_map(|(key, value)| match value {
+            FieldValue::Float(v) if v.is_nan() => None,
+            FieldValue::Float(v) => Some(format!("{}={v}", escape(key))),
+            FieldValue::Integer(v) => Some(format!("{}={v}i", escape(key))),
+        })
+        .collect::<Vec<_>>();
+    if fields.is_empty() {
+        return None;
+    }
+
+    let mut line = escape(point.measurement);
+    for (key, value) in &point.tags {
+        line.push_str(&format!(",{}={}", escape(key), escape(value)));
+    }
+    let timestamp = point.date.and_time(NaiveTime::MIN).and_utc().timestamp();
+    Some(format!("{line} {} {timestamp}", fields.join(",")))
+}
+
+/// Escapes the characters that have a meaning in line protocol.
+fn escape(str: &str) -> String {
+    str.replace('\\', "\\\\")
+        .replace(',', "\\,")
+        .replace('=', "\\=")
+        .replace(' ', "\\ ")
+}
@@ -0,0 +1,246 @@
+//! Writes one CSV row per colony per game day to a single file per run, meant
+//! to be loaded straight into a data frame.
+//!
+//! The file is called `<config name>_seed<seed>.csv`, followed by the
+//! [`OutputSuffix`] of the run if it has one, and is placed in the
+//! `directory` of the [`TimeSeriesConfig`]. A run continued from a snapshot
+//! appends to the file of the run it was started from.
+
+use std::{
+    collections::HashMap,
+    fs::{self, File, OpenOptions},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use bevy::{app::AppExit, ecs::event::ManualEventReader, prelude::*};
+
+use crate::{
+    config::{SelectedConfigPath, ThirdLifeConfig},
+    headless::OutputSuffix,
+    rng::SimRng,
+    time::{DailyTick, GameDate},
+    worlds::{
+        env_and_infra::components::SanitationInfrastructure,
+        food::components::{CarbResource, FoodResource, MeatResource, ResourceOf},
+        population::{
+            components::Population,
+            events::{CitizenCreated, CitizenDied, DeathReason},
+            init_citizens,
+        },
+        snapshot::{restore_snapshot, LoadedSnapshot},
+        stability::components::Stability,
+        wealth::components::WealthAndSpending,
+        SimulationSet, WorldEntity,
+    },
+    SimulationState,
+};
+
+use super::csv::quote;
+
+const HEADER: &[&str] = &[
+    "date", "world",
+    "population_count", "working_pop", "younglings", "retirees", "average_age",
+    "average_children_per_mother",
+    "food", "carb", "meat",
+    "total_wealth", "spending_available", "civil_spending", "sanitation_spending",
+    "social_spending", "environmental_spending",
+    "health_index_score", "live_birth_mortality_rate",
+    "births", "deaths_old_age", "deaths_starvation", "deaths_infant", "deaths_plague",
+    "stability", "growth", "death_rate", "days_until_starvation", "days_to_collapse",
+];
+
+pub struct TimeSeriesPlugin;
+
+impl Plugin for TimeSeriesPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(
+                OnEnter(SimulationState::Running),
+                init_time_series.after(init_citizens).after(restore_snapshot)
+            )
+            .add_systems(
+                DailyTick,
+                write_day
+                    .after(SimulationSet::Statistics)
+                    .run_if(resource_exists::<TimeSeries>)
+            )
+            .add_systems(Last, flush_on_exit.run_if(resource_exists::<TimeSeries>));
+    }
+}
+
+/// The file of the current run.
+#[derive(Resource)]
+pub struct TimeSeries {
+    writer: BufWriter<File>,
+    flush_interval: u32,
+    days_since_flush: u32,
+    /// Kept here instead of in an [`EventReader`] so the citizens spawned at
+    /// the start of the run are skipped and not counted as births.
+    births: ManualEventReader<CitizenCreated>,
+}
+
+impl TimeSeries {
+    fn create(path: &Path, append: bool, flush_interval: u32) -> io::Result<Self> {
+        if let Some(folder) = path.parent() {
+            fs::create_dir_all(folder)?;
+        }
+        let write_header = !append || !path.exists();
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(append)
+            .truncate(!append)
+            .open(path)?;
+        let mut writer = BufWriter::new(file);
+        if write_header {
+            writeln!(writer, "{}", HEADER.join(","))?;
+        }
+        Ok(Self { writer, flush_interval, days_since_flush: 0, births: default() })
+    }
+}
+
+fn init_time_series(
+    mut commands: Commands,
+    config: Res<ThirdLifeConfig>,
+    config_path: Res<SelectedConfigPath>,
+    sim_rng: Res<SimRng>,
+    suffix: Option<Res<OutputSuffix>>,
+    snapshot: Option<Res<LoadedSnapshot>>,
+    births: Res<Events<CitizenCreated>>,
+) {
+    let Some(time_series_config) = config.time_series() else {
+        return;
+    };
+    let path = PathBuf::from(time_series_config.directory())
+        .join(format!("{}_seed{}.csv", config_path.name(), sim_rng.seed()));
+    let path = OutputSuffix::apply(suffix.as_deref(), path);
+    let flush_interval = time_series_config.flush_interval().max(1);
+
+    match TimeSeries::create(&path, snapshot.is_some(), flush_interval) {
+        Ok(mut time_series) => {
+            time_series.births = births.get_reader_current();
+            info!("Writing the time series of the run to {}", path.display());
+            commands.insert_resource(time_series);
+        }
+        Err(err) => error!("Could not create {}, no time series is written: {err}", path.display()),
+    }
+}
+
+type ColonyStatistics<'a> = (
+    Entity,
+    &'a WorldEntity,
+    &'a Population,
+    &'a WealthAndSpending,
+    &'a SanitationInfrastructure,
+    &'a Stability,
+);
+type ColonyResources<'a> = (
+    &'a ResourceOf, Option<&'a FoodResource>, Option<&'a CarbResource>, Option<&'a MeatResource>
+);
+
+#[derive(Default)]
+struct DailyEvents {
+    births: usize,
+    old_age: usize,
+    starvation: usize,
+    infant: usize,
+    plague: usize,
+}
+
+fn write_day(
+    mut time_series: ResMut<TimeSeries>,
+    game_date: Res<GameDate>,
+    created: Res<Events<CitizenCreated>>,
+    mut deaths: EventReader<CitizenDied>,
+    colonies: Query<ColonyStatistics>,
+    resources: Query<ColonyResources>,
+) {
+    let mut events = HashMap::<Entity, DailyEvents>::new();
+    for created in time_series.births.read(&created).filter(|c| c.age == 0) {
+        events.entry(created.colony).or_default().births += 1;
+    }
+    for died in deaths.read() {
+        let colony = events.entry(died.colony).or_default();
+        match died.reason {
+            DeathReason::OldAge => colony.old_age += 1,
+            DeathReason::Starvation => colony.starvation += 1,
+            DeathReason::InfantDeath => colony.infant += 1,
+            DeathReason::Plague => colony.plague += 1,
+        }
+    }
+
+    let mut stocks = HashMap::<Entity, [f32; 3]>::new();
+    for (of, food, carb, meat) in &resources {
+        let stock = stocks.entry(of.colony).or_default();
+        stock[0] += food.map_or(0., |r| r.amount);
+        stock[1] += carb.map_or(0., |r| r.amount);
+        stock[2] += meat.map_or(0., |r| r.amount);
+    }
+
+    let date = game_date.date;
+    let mut result = Ok(());
+    for (colony, world, population, wealth, sanitation, stability) in &colonies {
+        let day = events.remove(&colony).unwrap_or_default();
+        let [food, carb, meat] = stocks.get(&colony).copied().unwrap_or_default();
+        let row = [
+            date.to_string(),
+            quote(world.name()),
+            population.count.to_string(),
+            population.working_pop.to_string(),
+            population.younglings.to_string(),
+            population.retirees.to_string(),
+            population.average_age.to_string(),
+            float(population.average_children_per_mother),
+            float(food),
+            float(carb),
+            float(meat),
+            float(wealth.total_wealth),
+            float(wealth.spending_available),
+            float(wealth.total_civil_spending()),
+            float(wealth.total_sanitation_spending()),
+            float(wealth.total_social_spending()),
+            float(wealth.total_environmental_spending()),
+            float(sanitation.health_index_score),
+            float(sanitation.live_birth_mortality_rate),
+            day.births.to_string(),
+            day.old_age.to_string(),
+            day.starvation.to_string(),
+            day.infant.to_string(),
+            day.plague.to_string(),
+            stability.label.to_string(),
+            stability.growth.to_string(),
+            stability.death_rate.to_string(),
+            stability.days_until_starvation.map_or(String::new(), |d| d.to_string()),
+            stability.days_to_collapse.map_or(String::new(), |d| d.to_string()),
+        ];
+        result = result.and_then(|_| writeln!(time_series.writer, "{}", row.join(",")));
+    }
+
+    time_series.days_since_flush += 1;
+    if time_series.days_since_flush >= time_series.flush_interval {
+        time_series.days_since_flush = 0;
+        result = result.and_then(|_| time_series.writer.flush());
+    }
+    if let Err(err) = result {
+        error!("Writing the time series of {date} failed: {err}");
+    }
+}
+
+/// `NaN` is written as an empty cell so it is read back as a missing value.
+fn float(value: f32) -> String {
+    if value.is_nan() {
+        String::new()
+    } else {
+        value.to_string()
+    }
+}
+
+fn flush_on_exit(mut time_series: ResMut<TimeSeries>, exit: EventReader<AppExit>) {
+    if exit.is_empty() {
+        return;
+    }
+    if let Err(err) = time_series.writer.flush() {
+        error!("Flushing the time series failed: {err}");
+    }
+}
@@ -0,0 +1,194 @@
+//! All random decisions of the simulation go through [`SimRng`] so that a run
+//! can be reproduced from its seed.
+//!
+//! The seed is taken from [`SeedOverride`] if it was given on the command
+//! line, otherwise from the `seed` in the [`ThirdLifeConfig`]. If neither is
+//! set a random seed is chosen and logged so the run can still be repeated.
+//!
+//! Every colony gets its own stream of the generator, meaning that what
+//! happens in one colony does not change the random numbers of another one.
+//! Systems drawing from the same colony stream still have to be ordered
+//! explicitly, otherwise the order in which they draw is up to bevy.
+
+use bevy::{prelude::*, utils::HashMap};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use crate::{
+    config::ThirdLifeConfig,
+    worlds::{config::{WorldConfig, WorldsConfig}, init_colonies, snapshot::LoadedSnapshot, WorldColony},
+    SimulationState,
+};
+
+pub struct SimRngPlugin;
+
+impl Plugin for SimRngPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(SimulationState::Running),
+            init_sim_rng
+                .after(init_colonies)
+                .run_if(not(resource_exists::<LoadedSnapshot>))
+        );
+    }
+}
+
+/// Seed provided through the command line. Takes precedence over the seed in
+/// the config file.
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct SeedOverride(pub u64);
+
+#[derive(Resource)]
+pub struct SimRng {
+    seed: u64,
+    colonies: HashMap<Entity, ChaCha8Rng>,
+}
+
+impl SimRng {
+    pub fn new(seed: u64) -> Self {
+        Self { seed, colonies: HashMap::new() }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Creates the stream of a colony. Streams are identified by a number so
+    /// the same colony should always get the same one, otherwise the run can't
+    /// be reproduced.
+    pub fn add_colony(&mut self, colony: Entity, stream: u64) {
+        let mut rng = ChaCha8Rng::seed_from_u64(self.seed);
+        rng.set_stream(stream);
+        self.colonies.insert(colony, rng);
+    }
+
+    /// Sets the stream of a colony to an existing generator, used to continue
+    /// a run from a [`crate::worlds::snapshot::Snapshot`].
+    pub fn insert_colony(&mut self, colony: Entity, rng: ChaCha8Rng) {
+        self.colonies.insert(colony, rng);
+    }
+
+    /// The random number generator of the given colony.
+    pub fn colony(&mut self, colony: Entity) -> &mut ChaCha8Rng {
+        let Some(rng) = self.colonies.get_mut(&colony) else {
+            panic!(r#"
+                A random number was requested for a colony that has no stream
+                in the `SimRng`, make sure the system runs after `init_sim_rng`.
+            "#);
+        };
+        rng
+    }
+}
+
+/// Creates the streams for every colony, the stream number is the position
+/// of the world in the `worlds` config so it does not depend on the order
+/// in which the entities are spawned. When starting from a snapshot the
+/// streams are restored from it instead.
+pub(crate) fn init_sim_rng(
+    mut commands: Commands,
+    config: Res<ThirdLifeConfig>,
+    worlds_config: Res<WorldsConfig>,
+    seed_override: Option<Res<SeedOverride>>,
+    colonies: Query<(Entity, &WorldConfig), With<WorldColony>>,
+) {
+    let seed = seed_override.map(|s| s.0)
+        .or(config.seed())
+        .unwrap_or_else(rand::random);
+    info!("Simulation seed: {seed}");
+
+    let mut sim_rng = SimRng::new(seed);
+    for (colony, world) in &colonies {
+        let stream = worlds_config.worlds().iter()
+            .position(|w| w.name() == world.name())
+            .unwrap();
+        sim_rng.add_colony(colony, stream as u64);
+    }
+    commands.insert_resource(sim_rng);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use bevy::app::{AppExit, PluginsState};
+    use chrono::{Days, NaiveDate};
+
+    use crate::{
+        headless::{build_app, RunLength},
+        time::GameDate,
+        worlds::{
+            food::components::{CarbResource, FoodResource, MeatResource, ResourceOf},
+            population::components::{Citizen, CitizenOf},
+        },
+    };
+
+    use super::*;
+
+    /// The citizens with their birthdays and the food, carb and meat stocks
+    /// of every world by name.
+    type WorldState = Vec<(String, Vec<NaiveDate>, f32, f32, f32)>;
+
+    /// Runs `config-1` headless with the given seed for `days` game days.
+    fn simulate(seed: u64, days: u64) -> WorldState {
+        let mut app = build_app(Some("config-1".to_string()), RunLength::Days(days));
+        app.insert_resource(SeedOverride(seed));
+        while app.plugins_state() == PluginsState::Adding {
+            bevy::tasks::tick_global_task_pools_on_main_thread();
+        }
+        app.finish();
+        app.cleanup();
+
+        // INFO: The config is loaded in the background, so the amount of
+        // updates until the run starts depends on the machine.
+        let timeout = Instant::now() + Duration::from_secs(60);
+        let end = loop {
+            app.update();
+            if let Some(date) = app.world.get_resource::<GameDate>() {
+                break date.date + Days::new(days);
+            }
+            assert!(Instant::now() < timeout, "the config was not loaded in time");
+            std::thread::sleep(Duration::from_millis(1));
+        };
+        // INFO: The run can also end early, for example once every colony
+        // died out.
+        while app.world.resource::<GameDate>().date < end
+            && app.world.resource::<Events<AppExit>>().is_empty()
+        {
+            app.update();
+        }
+
+        let mut stocks = HashMap::<Entity, [f32; 3]>::new();
+        let mut resources = app.world.query::<(
+            &ResourceOf, Option<&FoodResource>, Option<&CarbResource>, Option<&MeatResource>
+        )>();
+        for (of, food, carb, meat) in resources.iter(&app.world) {
+            let stock = stocks.entry(of.colony).or_default();
+            stock[0] += food.map_or(0., |r| r.amount);
+            stock[1] += carb.map_or(0., |r| r.amount);
+            stock[2] += meat.map_or(0., |r| r.amount);
+        }
+        let mut birthdays = HashMap::<Entity, Vec<NaiveDate>>::new();
+        let mut citizens = app.world.query::<(&CitizenOf, &Citizen)>();
+        for (of, citizen) in citizens.iter(&app.world) {
+            birthdays.entry(of.colony).or_default().push(citizen.birthday);
+        }
+        let mut colonies = app.world.query::<(Entity, &WorldConfig)>();
+        let mut worlds = colonies.iter(&app.world)
+            .map(|(colony, world)| {
+                let mut birthdays = birthdays.remove(&colony).unwrap_or_default();
+                birthdays.sort();
+                let [food, carb, meat] = stocks.get(&colony).copied().unwrap_or_default();
+                (world.name(), birthdays, food, carb, meat)
+            })
+            .collect::<WorldState>();
+        worlds.sort_by(|a, b| a.0.cmp(&b.0));
+        worlds
+    }
+
+    #[test]
+    fn same_seed_same_run() {
+        let first = simulate(7, 30);
+        assert_eq!(first.len(), 3);
+        assert_eq!(first, simulate(7, 30));
+    }
+}
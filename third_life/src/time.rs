@@ -1,30 +1,63 @@
 use crate::{config::ThirdLifeConfig, SimulationState};
-use bevy::prelude::*;
+use bevy::{ecs::schedule::ScheduleLabel, prelude::*};
 use chrono::{prelude::*, Duration};
+use serde::{Deserialize, Serialize};
 
 pub struct TimeDatePlugin;
 
 impl Plugin for TimeDatePlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
+        app.init_schedule(DailyTick)
+        .add_systems(
             OnEnter(SimulationState::Running),
             (init_day_length, init_start_date),
         )
+        .init_resource::<SimulationSpeed>()
         .add_systems(
             Update,
             update_date.run_if(in_state(SimulationState::Running)),
         )
         .add_event::<DateChanged>()
-        .add_event::<MonthChanged>();
+        .add_event::<WeekChanged>()
+        .add_event::<MonthChanged>()
+        .add_event::<SeasonChanged>()
+        .add_event::<YearChanged>();
     }
 }
 
+/// Schedule containing everything that happens once per game day. It is run
+/// by `update_date` right after the date moved forward, so the systems in it
+/// don't depend on the frame rate and can rely on exactly one day having
+/// passed. The phases of a day are ordered with
+/// [`crate::worlds::SimulationSet`].
+#[derive(ScheduleLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DailyTick;
+
+/// How much real time passes for every day of the simulation.
 #[derive(Resource)]
-struct DayLength {
-    timer: Timer,
+pub struct DayLength {
+    pub timer: Timer,
+}
+
+/// How fast the game days pass. Every simulated day runs the [`DailyTick`]
+/// schedule and sends its own [`DateChanged`], [`WeekChanged`],
+/// [`MonthChanged`], [`SeasonChanged`] and [`YearChanged`] events, no matter
+/// how many days are simulated in a single frame.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SimulationSpeed {
+    /// One day every `real_time_day_length` seconds.
+    #[default]
+    RealTime,
+    /// No days pass.
+    Paused,
+    /// Simulates a single day on the next frame and pauses afterwards.
+    Step,
+    /// Simulates the given amount of days every frame, regardless of how
+    /// much real time has passed.
+    DaysPerFrame(u32),
 }
 
-fn init_day_length(mut commands: Commands, config: Res<ThirdLifeConfig>) {
+pub(crate) fn init_day_length(mut commands: Commands, config: Res<ThirdLifeConfig>) {
     commands.insert_resource(DayLength {
         timer: Timer::from_seconds(config.real_time_day_length(), TimerMode::Repeating),
     });
@@ -35,35 +68,69 @@ pub struct GameDate {
     pub date: NaiveDate,
 }
 
-fn init_start_date(mut commands: Commands, config: Res<ThirdLifeConfig>) {
-    commands.insert_resource(GameDate {
-        date: NaiveDate::from_ymd_opt(
-            config.starting_day().year(),
-            config.starting_day().month(),
-            config.starting_day().day(),
-        )
-        .unwrap(),
-    })
+pub(crate) fn init_start_date(mut commands: Commands, config: Res<ThirdLifeConfig>) {
+    let date = NaiveDate::from_ymd_opt(
+        config.starting_day().year(),
+        config.starting_day().month(),
+        config.starting_day().day(),
+    )
+    .unwrap();
+    commands.insert_resource(GameDate { date });
+    commands.insert_resource(Season::of(date));
 }
 
-fn update_date(
-    time: Res<Time>,
-    mut day_length: ResMut<DayLength>,
-    mut game_date: ResMut<GameDate>,
-    mut date_changed_writer: EventWriter<DateChanged>,
-    mut month_changed_writer: EventWriter<MonthChanged>,
-) {
-    day_length.timer.tick(time.delta());
+fn update_date(world: &mut World) {
+    let delta = world.resource::<Time>().delta();
+    let speed = *world.resource::<SimulationSpeed>();
 
-    if day_length.timer.finished() {
-        game_date.date = game_date.date + Duration::days(1);
+    let days = match speed {
+        SimulationSpeed::RealTime => {
+            let mut day_length = world.resource_mut::<DayLength>();
+            day_length.timer.tick(delta).finished() as u32
+        }
+        SimulationSpeed::Paused => 0,
+        SimulationSpeed::Step => {
+            world.insert_resource(SimulationSpeed::Paused);
+            1
+        }
+        SimulationSpeed::DaysPerFrame(days) => days,
+    };
 
-        if game_date.date.month() != game_date.date.pred_opt().unwrap().month() {
-            month_changed_writer.send(MonthChanged);
+    for _ in 0..days {
+        advance_day(world);
+        // INFO: A system of the day can pause the simulation, for example
+        // once the run ended. The rest of the days of this frame are dropped.
+        if *world.resource::<SimulationSpeed>() == SimulationSpeed::Paused {
+            break;
         }
+    }
+}
+
+/// Moves the date forward by a single day and runs everything that has to
+/// happen on that day.
+fn advance_day(world: &mut World) {
+    let mut game_date = world.resource_mut::<GameDate>();
+    game_date.date = game_date.date + Duration::days(1);
+    let date = game_date.date;
+    let previous = date.pred_opt().unwrap();
 
-        date_changed_writer.send(DateChanged::from_date(game_date.date));
+    if date.weekday() == Weekday::Mon {
+        world.send_event(WeekChanged { week: date.iso_week().week() });
+    }
+    if date.month() != previous.month() {
+        world.send_event(MonthChanged);
+    }
+    let season = Season::of(date);
+    if season != Season::of(previous) {
+        world.send_event(SeasonChanged { season });
     }
+    world.insert_resource(season);
+    if date.year() != previous.year() {
+        world.send_event(YearChanged { year: date.year() });
+    }
+
+    world.send_event(DateChanged::from_date(date));
+    world.run_schedule(DailyTick);
 }
 
 impl std::ops::Deref for GameDate {
@@ -84,5 +151,46 @@ impl DateChanged {
     }
 }
 
+/// Sent on every monday.
+#[derive(Event)]
+pub struct WeekChanged {
+    /// ISO week number of the new week.
+    pub week: u32,
+}
+
 #[derive(Event)]
 pub struct MonthChanged;
+
+/// Sent on the first day of a [`Season`] of the calendar. Worlds can have
+/// their seasons shifted, see [`crate::worlds::seasons`].
+#[derive(Event)]
+pub struct SeasonChanged {
+    pub season: Season,
+}
+
+#[derive(Event)]
+pub struct YearChanged {
+    pub year: i32,
+}
+
+/// Meteorological season of the [`GameDate`] on the northern hemisphere,
+/// which starts spring in March.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Season {
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+impl Season {
+    pub fn of(date: NaiveDate) -> Self {
+        match date.month() {
+            3..=5 => Season::Spring,
+            6..=8 => Season::Summer,
+            9..=11 => Season::Autumn,
+            _ => Season::Winter,
+        }
+    }
+}
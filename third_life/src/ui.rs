@@ -1,11 +1,38 @@
+use std::path::PathBuf;
+
 use bevy::prelude::*;
-use bevy_egui::{EguiContexts, EguiSettings};
+use bevy_egui::{egui::{Slider, Window}, EguiContexts, EguiSettings};
+
+use crate::{
+    config::{
+        editor::{show_config_editor, ConfigEditor},
+        show_config_selection, SelectedConfigPath, UnappliedConfigChanges,
+    },
+    end_conditions::FinalReport,
+    time::{GameDate, SimulationSpeed},
+    worlds::{snapshot::SaveSnapshot, ui::WorldsUiPlugin},
+    SimulationState,
+};
 
+/// Everything that needs egui. Kept seperate from the simulation plugins so
+/// that the simulation can also run without a window.
 pub struct ThridLifeUiPlugin;
 
 impl Plugin for ThridLifeUiPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, configure_visuals_system);
+        app.add_systems(Startup, configure_visuals_system)
+            .init_resource::<ConfigEditor>()
+            .add_systems(Update, (show_config_selection, show_config_editor).run_if(
+                in_state(SimulationState::ConfigSelection)
+                    .and_then(not(resource_exists::<SelectedConfigPath>))
+            ))
+            .add_systems(Update, show_simulation_controls.run_if(in_state(SimulationState::Running)))
+            .add_systems(Update, show_final_report.run_if(resource_exists::<FinalReport>))
+            .add_systems(Update, show_unapplied_config_changes.run_if(
+                in_state(SimulationState::Running)
+                    .and_then(|changes: Res<UnappliedConfigChanges>| !changes.0.is_empty())
+            ))
+            .add_plugins(WorldsUiPlugin);
     }
 }
 
@@ -23,3 +50,106 @@ fn configure_visuals_system(
 
     egui_settings.scale_factor = 0.8;
 }
+
+/// Days per frame used when fast forwarding, kept around so switching to
+/// another speed and back doesn't reset the slider.
+struct FastForwardDays(u32);
+
+impl Default for FastForwardDays {
+    fn default() -> Self {
+        Self(30)
+    }
+}
+
+/// Controls of the running simulation, its speed and saving snapshots. The
+/// snapshots are saved to `snapshots/` named by the current game date.
+fn show_simulation_controls(
+    mut contexts: EguiContexts,
+    mut speed: ResMut<SimulationSpeed>,
+    mut fast_forward: Local<FastForwardDays>,
+    mut save: EventWriter<SaveSnapshot>,
+    game_date: Res<GameDate>,
+) {
+    Window::new("Simulation").show(contexts.ctx_mut(), |ui| {
+        ui.label(format!("Date: {}", game_date.date));
+        ui.horizontal(|ui| {
+            if ui.selectable_label(*speed == SimulationSpeed::Paused, "Pause").clicked() {
+                *speed = SimulationSpeed::Paused;
+            }
+            if ui.button("Step one day").clicked() {
+                *speed = SimulationSpeed::Step;
+            }
+            if ui.selectable_label(*speed == SimulationSpeed::RealTime, "Real time").clicked() {
+                *speed = SimulationSpeed::RealTime;
+            }
+            let fast = matches!(*speed, SimulationSpeed::DaysPerFrame(_));
+            if ui.selectable_label(fast, "Fast forward").clicked() {
+                *speed = SimulationSpeed::DaysPerFrame(fast_forward.0);
+            }
+        });
+        let slider = Slider::new(&mut fast_forward.0, 1..=365).text("days per frame");
+        if ui.add(slider).changed() && matches!(*speed, SimulationSpeed::DaysPerFrame(_)) {
+            *speed = SimulationSpeed::DaysPerFrame(fast_forward.0);
+        }
+        ui.separator();
+        if ui.button("Save snapshot").clicked() {
+            let path = PathBuf::from(format!("snapshots/{}.json", game_date.date));
+            save.send(SaveSnapshot { path });
+        }
+    });
+}
+
+/// Lists the changes to the config files that only take effect on the next
+/// run, until they are dismissed.
+fn show_unapplied_config_changes(
+    mut contexts: EguiContexts,
+    mut changes: ResMut<UnappliedConfigChanges>,
+) {
+    Window::new("Config changes for the next run").show(contexts.ctx_mut(), |ui| {
+        for change in changes.0.iter() {
+            ui.label(change);
+        }
+        if ui.button("Dismiss").clicked() {
+            changes.0.clear();
+        }
+    });
+}
+
+/// Summary of the run once one of the end conditions was met. The simulation
+/// is paused at that point but can be continued with the controls.
+fn show_final_report(mut contexts: EguiContexts, report: Res<FinalReport>) {
+    Window::new("Simulation ended").show(contexts.ctx_mut(), |ui| {
+        ui.label(format!("Ended on {}: {}", report.date, report.reason));
+        for colony in &report.colonies {
+            let demographics = &colony.demographics;
+            let food = &colony.food;
+            let wealth = &colony.wealth;
+            ui.separator();
+            ui.heading(&colony.world);
+            ui.label(format!(
+                "Population: {} ({} men, {} women, {} younglings, {} retirees)",
+                demographics.population, demographics.men, demographics.women,
+                demographics.younglings, demographics.retirees
+            ));
+            ui.label(format!("Average age: {:.1}", demographics.average_age));
+            ui.label(format!(
+                "Children per mother: {:.2}", demographics.average_children_per_mother
+            ));
+            ui.label(format!(
+                "Births: {}, deaths: {} of old age, {} starved, {} infants, {} of the plague",
+                demographics.births, demographics.deaths_old_age, demographics.deaths_starvation,
+                demographics.deaths_infant, demographics.deaths_plague
+            ));
+            ui.label(format!(
+                "Food: {:.0} -> {:.0}, carbs: {:.0} -> {:.0}, meat: {:.0} -> {:.0}, {} starving",
+                food.start.food, food.end.food, food.start.carb, food.end.carb,
+                food.start.meat, food.end.meat, food.starving
+            ));
+            ui.label(format!(
+                "Wealth: {:.0}, spending available: {:.0}, health index: {:.2}",
+                wealth.total_wealth, wealth.spending_available, wealth.health_index_score
+            ));
+            ui.label(format!("Stability: {}", colony.stability.label));
+        }
+    });
+}
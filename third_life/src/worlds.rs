@@ -1,30 +1,69 @@
+pub mod calendar;
 pub mod config;
-mod food;
-mod population;
-mod ui;
-mod env_and_infra;
-mod wealth;
+pub mod food;
+pub mod population;
+pub mod reload;
+pub(crate) mod ui;
+pub mod snapshot;
+pub mod scenario;
+pub mod seasons;
+pub mod stability;
+pub mod env_and_infra;
+pub mod wealth;
 
 
 
 use bevy::{prelude::*, ecs::world};
 
-use crate::{
-    SimulationState, animation::{AnimationIndex, SpriteSize, AnimationTimer, ColonyAnimationBundle}, config::SelectedConfigPath,
-};
+use crate::{rng::SimRngPlugin, time::DailyTick, SimulationState};
 
 use self::{
-    config::{SpriteConfig, WorldConfig, WorldsConfig, WorldsConfigPlugin}, env_and_infra::{components::ColonyInfraAndEnvBundle, InfrastructurePlugin}, food::FoodPlugin, population::{components::Population, PopulationPlugin}, ui::WorldsUiPlugin, wealth::{components::{ColonyWealthBundle, WealthAndSpending}, WealthPlugin}
+    calendar::{CalendarPlugin, LocalCalendar}, config::{WorldConfig, WorldsConfig, WorldsConfigPlugin}, env_and_infra::{components::ColonyInfraAndEnvBundle, InfrastructurePlugin}, food::FoodPlugin, population::{components::Population, PopulationPlugin}, reload::ReloadPlugin, scenario::ScenarioPlugin, seasons::SeasonsPlugin, snapshot::{LoadedSnapshot, SnapshotPlugin}, stability::StabilityPlugin, wealth::{components::ColonyWealthBundle, WealthPlugin}
 };
 
+/// Phases of a game day in the [`DailyTick`] schedule, they run in the
+/// order in which they are declared. Systems within a phase that depend on
+/// each other still have to be ordered by the plugin adding them.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SimulationSet {
+    /// Farms producing raw resources.
+    Production,
+    /// Turning raw resources into food.
+    Processing,
+    /// Citizens eating.
+    Consumption,
+    /// Births, deaths and relationships.
+    Lifecycle,
+    /// Wealth and the spending that depends on it.
+    Economy,
+    /// Aggregating the state of the day, nothing in here should change the
+    /// outcome of the simulation.
+    Statistics,
+}
+
 pub struct WorldsPlugin;
 
 impl Plugin for WorldsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(SimulationState::Running), init_colonies)
+        app.add_systems(
+                OnEnter(SimulationState::Running),
+                init_colonies.run_if(not(resource_exists::<LoadedSnapshot>))
+            )
+            .configure_sets(
+                DailyTick,
+                (
+                    SimulationSet::Production,
+                    SimulationSet::Processing,
+                    SimulationSet::Consumption,
+                    SimulationSet::Lifecycle,
+                    SimulationSet::Economy,
+                    SimulationSet::Statistics,
+                ).chain()
+            )
             .add_plugins((
-                WorldsConfigPlugin, PopulationPlugin, FoodPlugin, WorldsUiPlugin, 
-                InfrastructurePlugin, WealthPlugin
+                WorldsConfigPlugin, SimRngPlugin, PopulationPlugin, FoodPlugin,
+                InfrastructurePlugin, WealthPlugin, SnapshotPlugin, ScenarioPlugin, StabilityPlugin,
+                SeasonsPlugin, CalendarPlugin, ReloadPlugin
             ));
 
     }
@@ -32,35 +71,12 @@ impl Plugin for WorldsPlugin {
 
 
 
-fn init_colonies(
+pub(crate) fn init_colonies(
     mut commands: Commands,
     worlds_config: Res<WorldsConfig>,
-    asset_server: Res<AssetServer>,
-    config_path: Res<SelectedConfigPath>,
-    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
 ) {
-    let font = asset_server.load("fonts/VictorMonoNerdFontMono-Medium.ttf");
     for world in worlds_config.worlds() {
-        let texture = asset_server.load(format!(
-            "{}/sprite_sheets/{}",
-            config_path.0,
-            world.sprite().sprite_sheet()
-        ));
-        commands.spawn(WorldColonyBundle::new(
-                texture,
-                &mut texture_atlas_layouts,
-                world.clone()
-        )).with_children(|parent| {
-            parent.spawn(
-                Text2dBundle {
-                    text: Text::from_section(world.name(), TextStyle { 
-                        font: font.clone(), font_size: 24., color: Color::WHITE 
-                    }).with_justify(JustifyText::Center),
-                    transform: Transform::from_xyz(0.,-1. * world.sprite().shape().0 as f32, 0.),
-                    ..default()
-                }
-            );
-        });
+        commands.spawn(WorldColonyBundle::new(world.clone()));
     }
 }
 
@@ -76,6 +92,10 @@ impl WorldEntity {
     fn new(name: String) -> Self {
         WorldEntity { name }
     }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
 }
 
 #[derive(Component)]
@@ -86,29 +106,21 @@ pub struct WorldColonyBundle {
     colony: WorldColony,
     entity: WorldEntity,
     population: Population,
-    animation: ColonyAnimationBundle,
     wealth: ColonyWealthBundle,
     infra_and_env: ColonyInfraAndEnvBundle,
+    calendar: LocalCalendar,
     config: WorldConfig
 }
 
 impl WorldColonyBundle {
-    pub fn new(
-        sprite_sheet: Handle<Image>,
-        mut texture_atlas_layouts: &mut ResMut<Assets<TextureAtlasLayout>>,
-        world: WorldConfig
-    ) -> Self {
+    pub fn new(world: WorldConfig) -> Self {
         Self { 
             colony: WorldColony,
             entity: WorldEntity::new(world.name()),
             population: Population::default(),
-            animation: ColonyAnimationBundle::new(
-                world.name(), world.world_position(), 
-                sprite_sheet, texture_atlas_layouts,
-                world.sprite()
-            ),
             wealth: ColonyWealthBundle::new(world.government()),
             infra_and_env: ColonyInfraAndEnvBundle::default(),
+            calendar: LocalCalendar::new(&world.calendar()),
             config: world
         }
     }
@@ -0,0 +1,89 @@
+//! Local days and years of the individual worlds.
+//!
+//! The [`crate::time::GameDate`] is the master clock of the simulation and
+//! moves forward by one day per [`DailyTick`]. Every world counts its own
+//! local days on top of it, with a length set by the `calendar` of its
+//! [`super::config::WorldConfig`]. A world with longer days has a
+//! [`LocalDayChanged`] on fewer game days, one with shorter days can have
+//! more than one on the same game day.
+//!
+//! Systems that act once per day of a colony, like eating and working the
+//! farms, run once for every [`LocalDayChanged`] of that colony. With the
+//! default length of `1.0` every world has exactly one local day per game
+//! day.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::time::DailyTick;
+
+use super::{config::CalendarConfig, SimulationSet};
+
+pub struct CalendarPlugin;
+
+impl Plugin for CalendarPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<LocalDayChanged>()
+            .add_systems(DailyTick, advance_local_days.before(SimulationSet::Production));
+    }
+}
+
+/// Days and years that passed on a world since the run started.
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct LocalCalendar {
+    day_length: f64,
+    year_length: f64,
+    /// Game days that passed since the last local day started.
+    elapsed: f64,
+    /// Amount of local days that passed.
+    pub day: u64,
+}
+
+impl LocalCalendar {
+    /// The lengths of the config are positive, which is checked while the
+    /// config is loaded.
+    pub fn new(config: &CalendarConfig) -> Self {
+        Self {
+            day_length: config.day_length(),
+            year_length: config.year_length(),
+            elapsed: 0.,
+            day: 0,
+        }
+    }
+
+    /// Local year the world is in, counted from `0`.
+    pub fn year(&self) -> u64 {
+        (self.day as f64 / self.year_length) as u64
+    }
+
+    /// Day within the current local year, counted from `0`.
+    pub fn day_of_year(&self) -> u64 {
+        self.day - (self.year() as f64 * self.year_length).ceil() as u64
+    }
+}
+
+/// Sent on every local day of a colony.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct LocalDayChanged {
+    pub colony: Entity,
+    /// Amount of local days that passed, including this one.
+    pub day: u64,
+    pub year: u64,
+}
+
+fn advance_local_days(
+    mut local_days: EventWriter<LocalDayChanged>,
+    mut colonies: Query<(Entity, &mut LocalCalendar)>,
+) {
+    for (colony, mut calendar) in &mut colonies {
+        calendar.elapsed += 1.;
+        // INFO: Allows for a bit of rounding so a `day_length` like `0.1`
+        // doesn't lose a day every now and then.
+        while calendar.elapsed >= calendar.day_length - 1e-9 {
+            calendar.elapsed -= calendar.day_length;
+            calendar.day += 1;
+            local_days.send(LocalDayChanged { colony, day: calendar.day, year: calendar.year() });
+        }
+    }
+}
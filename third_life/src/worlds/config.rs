@@ -1,27 +1,165 @@
 #![allow(dead_code)]
 
 
+use std::collections::BTreeMap;
+
 use proc_macros::{ConfigFile, Config};
 use bevy::prelude::*;
-use serde::{Deserialize};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
 
-use crate::{config::{ConfigurationLoader}};
+use crate::config::{
+    error::{ConfigViolation, UNKNOWN_FIELD},
+    field_path,
+    shape::{ConfigShape, DescribeConfig},
+    ConfigFields, ConfigurationLoader,
+};
 impl ConfigurationLoader for WorldsConfig {
     fn path_with_name() -> &'static str {
         "worlds"
     }
+
+    fn resolve(value: &mut Value) -> Result<(), Vec<ConfigViolation>> {
+        resolve_templates(value)
+    }
+
+    /// Templates, and worlds that extend one, only have to contain the
+    /// fields they set themselves, the rest comes from the templates.
+    fn file_json_schema() -> Value {
+        let world = WorldConfig::shape();
+        let mut schema = Self::shape().json_schema();
+        let properties = &mut schema["properties"];
+        properties["worlds"]["items"] = json!({
+            "if": { "properties": { "extends": { "type": "string" } }, "required": ["extends"] },
+            "then": world.partial_json_schema(),
+            "else": world.json_schema(),
+        });
+        let templates = properties["templates"].as_object_mut().unwrap();
+        templates.insert("type".to_string(), Value::from("object"));
+        templates.insert("additionalProperties".to_string(), world.partial_json_schema());
+        schema
+    }
 }
 
-#[derive(Deserialize, Debug, Clone, Resource, ConfigFile, Default, Config)]
+#[derive(Serialize, Deserialize, Debug, Clone, Resource, ConfigFile, Default, Config)]
 pub struct WorldsConfig {
+    /// Parts of worlds, by name, that worlds can build on with `extends`.
+    /// They look like a world where every field can be left out.
+    #[def(BTreeMap::new())]
+    templates: Option<BTreeMap<String, Value>>,
     worlds: Vec<WorldConfig>
 }
 
-#[derive(Deserialize, Debug, Clone, Resource, Default, Config, Component)]
+/// Merges the template every world `extends` into it, the values of the
+/// world win over the ones of the template. Templates can extend other
+/// templates the same way.
+fn resolve_templates(file: &mut Value) -> Result<(), Vec<ConfigViolation>> {
+    let templates = file.get("templates")
+        .and_then(Value::as_object)
+        .cloned()
+        .unwrap_or_default();
+    let mut violations = Vec::new();
+    for (name, template) in &templates {
+        let path = field_path("templates", name);
+        if !template.is_object() {
+            violations.push(ConfigViolation { path, message: "has to be an object".to_string() });
+            continue;
+        }
+        let mut unknown = Vec::new();
+        WorldConfig::unknown_fields(template, &path, &mut unknown);
+        violations.extend(unknown.into_iter()
+            .map(|path| ConfigViolation { path, message: UNKNOWN_FIELD.to_string() }));
+    }
+    if !violations.is_empty() {
+        return Err(violations);
+    }
+
+    let worlds = file.get_mut("worlds").and_then(Value::as_array_mut).into_iter().flatten();
+    for (index, world) in worlds.enumerate() {
+        match extended(&templates, world, format!("worlds[{index}]")) {
+            Ok(Some(mut base)) => {
+                merge(&mut base, world.take());
+                *world = base;
+            },
+            Ok(None) => {},
+            // INFO: Worlds that extend the same broken template would all
+            // report it.
+            Err(violation) if violations.contains(&violation) => {},
+            Err(violation) => violations.push(violation),
+        }
+    }
+    match violations.is_empty() {
+        true => Ok(()),
+        false => Err(violations),
+    }
+}
+
+/// Everything `value` extends, with the templates further down the chain
+/// merged in below the closer ones. `None` if it extends nothing.
+fn extended(
+    templates: &Map<String, Value>, value: &Value, path: String
+) -> Result<Option<Value>, ConfigViolation> {
+    let mut base: Option<Value> = None;
+    let mut seen = Vec::new();
+    let (mut current, mut path) = (value, path);
+    while let Some(extends) = current.get("extends").filter(|e| !e.is_null()) {
+        let path_of_extends = field_path(&path, "extends");
+        let Some(name) = extends.as_str() else {
+            return Err(ConfigViolation {
+                path: path_of_extends,
+                message: "has to be the name of a template".to_string(),
+            });
+        };
+        if seen.contains(&name) {
+            return Err(ConfigViolation {
+                path: path_of_extends,
+                message: format!("the templates extend each other in a circle through `{name}`"),
+            });
+        }
+        let Some(template) = templates.get(name) else {
+            return Err(ConfigViolation {
+                path: path_of_extends,
+                message: format!("there is no template named `{name}`"),
+            });
+        };
+        seen.push(name);
+        base = Some(match base {
+            Some(closer) => {
+                let mut further = template.clone();
+                merge(&mut further, closer);
+                further
+            },
+            None => template.clone(),
+        });
+        current = template;
+        path = field_path("templates", name);
+    }
+    Ok(base)
+}
+
+/// Puts `over` on top of `base`, objects are merged field by field and
+/// anything else is replaced.
+fn merge(base: &mut Value, over: Value) {
+    match (base, over) {
+        (Value::Object(base), Value::Object(over)) => for (key, value) in over {
+            match base.get_mut(&key) {
+                Some(existing) => merge(existing, value),
+                None => { base.insert(key, value); },
+            }
+        },
+        (base, over) => *base = over,
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Resource, Default, Config, Component)]
 pub struct WorldConfig {
     /// Name should be unique, since its used for identification of multiple 
     /// things.
     name: String,
+    /// Name of the template in `templates` the world builds on.
+    #[def(None)]
+    extends: Option<Option<String>>,
     world_position: (isize, isize),
     #[def(PopulationConfig::def_conf())]
     population: Option<PopulationConfig>,
@@ -31,17 +169,47 @@ pub struct WorldConfig {
     environment: Option<EnvironmentConfig>,
     #[def(FoodConfig::def_conf())]
     food: Option<FoodConfig>,
+    /// When the seasons of the world begin, see [`crate::worlds::seasons`].
+    #[def(SeasonsConfig::def_conf())]
+    seasons: Option<SeasonsConfig>,
+    /// Length of the days and years of the world, see
+    /// [`crate::worlds::calendar`].
+    #[def(CalendarConfig::def_conf())]
+    calendar: Option<CalendarConfig>,
     sprite: SpriteConfig,
 }
 
+impl WorldConfig {
+    /// Takes over the parts of `new` that can change while the simulation
+    /// runs, which are the government, the environment and the seasons.
+    /// Returns the names of the other fields that differ, since they only
+    /// take effect on a new run.
+    pub fn apply_live(&mut self, new: &WorldConfig) -> Vec<&'static str> {
+        self.government = new.government.clone();
+        self.environment = new.environment.clone();
+        self.seasons = new.seasons.clone();
+        [
+            ("world_position", self.world_position != new.world_position),
+            ("population", self.population() != new.population()),
+            ("food", self.food() != new.food()),
+            ("calendar", self.calendar() != new.calendar()),
+            ("sprite", self.sprite != new.sprite),
+        ].into_iter().filter(|(_, differs)| *differs).map(|(field, _)| field).collect()
+    }
+}
+
 /// Different parameters affecting the population directly
-#[derive(Deserialize, Debug, Clone, Resource, Default, Config)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Resource, Default, Config)]
+#[validate(ages_are_ordered)]
+#[validate(pregnancy_term_is_ordered)]
 pub struct PopulationConfig {
     /// Starting number of Peple. Any Real number
     #[def(1000)]
+    #[min(1)]
     size: Option<u32>,
     /// Location of the Skew normal distribution. Any Positive number
     #[def(18.)]
+    #[min(0.)]
     location: Option<f32>,
     /// Scale of the Skew normal distribution. Any Real number
     #[def(6.)]
@@ -52,36 +220,93 @@ pub struct PopulationConfig {
     /// The spread in this case refers to at which age the probability of death
     /// starts to increase. Does not affect the actual average life expectancy.
     #[def(6.)]
-    life_expectancy_spread: Option<f32>
+    life_expectancy_spread: Option<f32>,
+    /// Age at which citizens come of age and can start working.
+    #[def(18)]
+    adult_age: Option<u32>,
+    /// Age at which citizens stop working.
+    #[def(65)]
+    retirement_age: Option<u32>,
+    /// Age women have to be older than to get married.
+    #[def(18)]
+    marriage_age: Option<u32>,
+    /// Days a citizen survives without a meal.
+    #[def(21)]
+    #[min(1)]
+    starvation_days: Option<usize>,
+    /// Food every citizen eats per day.
+    #[def(1.0)]
+    #[min(0.)]
+    daily_food_intake: Option<f32>,
+    /// Shortest and longest pregnancy in days, every pregnancy lasts
+    /// somewhere in between.
+    #[def((270, 280))]
+    pregnancy_term: Option<(i64, i64)>,
+}
+
+fn ages_are_ordered(config: &PopulationConfig) -> Result<(), String> {
+    match config.adult_age() <= config.retirement_age() {
+        true => Ok(()),
+        false => Err("`retirement_age` can't be lower than `adult_age`".to_string()),
+    }
 }
 
-#[derive(Deserialize, Debug, Clone, Resource, Default, Config)]
+fn pregnancy_term_is_ordered(config: &PopulationConfig) -> Result<(), String> {
+    match config.pregnancy_term() {
+        (shortest, longest) if 0 < shortest && shortest <= longest => Ok(()),
+        _ => Err("`pregnancy_term` has to be positive and start with the shortest term".to_string()),
+    }
+}
+
+/// The spendings are percentages of the budget and have to add up to 100.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Resource, Default, Config)]
+#[validate(spending_adds_up)]
 pub struct GovernmentConfig {
     #[def(0.1)]
+    #[min(0.)]
     citizen_payout: Option<f32>,
     #[def(25)]
+    #[range(0, 100)]
     civil_spending: Option<usize>,
     #[def(25)]
+    #[range(0, 100)]
     sanitation_spending: Option<usize>,
     #[def(25)]
+    #[range(0, 100)]
     social_spending: Option<usize>,
     #[def(25)]
+    #[range(0, 100)]
     environmental_spending: Option<usize>,
 }
 
+fn spending_adds_up(config: &GovernmentConfig) -> Result<(), String> {
+    let total = config.civil_spending() + config.sanitation_spending()
+        + config.social_spending() + config.environmental_spending();
+    match total {
+        100 => Ok(()),
+        total => Err(format!("spendings have to add up to 100, but add up to {total}")),
+    }
+}
+
 /// General factors of the environment of the world
-#[derive(Deserialize, Debug, Clone, Resource, Default, Config)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Resource, Default, Config)]
 pub struct EnvironmentConfig {
     #[def(0.5)]
+    #[range(0., 1.)]
     urbanization: Option<f32>,
     #[def(1.)]
+    #[range(0., 1.)]
     env_health: Option<f32>,
     #[def(1.)]
+    #[range(0., 1.)]
     ecosystem_vitylity: Option<f32>,
 }
 
 
-#[derive(Deserialize, Debug, Clone, Resource, Default, Config)]
+/// Farms of the world and how productive they are, see
+/// [`crate::worlds::food`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Resource, Default, Config)]
+#[validate(hours_are_positive)]
 pub struct FoodConfig {
     #[def(6)]
     cow_farms: Option<usize>,
@@ -91,9 +316,102 @@ pub struct FoodConfig {
     starting_beef: Option<f32>,
      #[def(5000.0)]
     starting_carb: Option<f32>,
+    /// Hectares of every wheat farm.
+    #[def(17.4)]
+    #[min(0.)]
+    wheat_farm_size: Option<f32>,
+    /// Hectares of every cow farm.
+    #[def(34.0)]
+    #[min(0.)]
+    cow_farm_size: Option<f32>,
+    /// Carbs a harvested hectare of wheat yields.
+    #[def(2670.0)]
+    #[min(0.)]
+    carbs_per_hectare: Option<f32>,
+    /// Cows every cow farm starts with, bulls included.
+    #[def(57)]
+    starting_cows: Option<usize>,
+    /// Cows a farm keeps for breeding, only the ones above are slaughtered.
+    #[def(47)]
+    min_cows: Option<usize>,
+    /// Meat a slaughtered cow yields.
+    #[def(250.0)]
+    #[min(0.)]
+    meat_per_cow: Option<f32>,
+    /// Hours a farmer works on a day.
+    #[def(8.0)]
+    farmer_hours_per_day: Option<f32>,
+    /// Hours of work it takes to slaughter a cow.
+    #[def(6.25)]
+    hours_per_cow: Option<f32>,
+    /// Amount of farmers every wheat farm tries to employ.
+    #[def(4)]
+    wheat_farm_workers: Option<usize>,
+    /// Amount of farmers every cow farm tries to employ.
+    #[def(4)]
+    cow_farm_workers: Option<usize>,
+    /// Every day `100 *` this much carb and meat are cooked into as much
+    /// food, as long as the colony has more of both.
+    #[def(5.0)]
+    #[min(0.)]
+    cook_multiplier: Option<f32>,
+}
+
+fn hours_are_positive(config: &FoodConfig) -> Result<(), String> {
+    match config.farmer_hours_per_day() > 0. && config.hours_per_cow() > 0. {
+        true => Ok(()),
+        false => Err("`farmer_hours_per_day` and `hours_per_cow` have to be positive".to_string()),
+    }
+}
+
+/// Shifts the seasons of a world against the ones of the calendar.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Resource, Default, Config)]
+pub struct SeasonsConfig {
+    /// Seasons of the southern hemisphere are half a year apart from the
+    /// northern ones.
+    #[def(Hemisphere::Northern)]
+    hemisphere: Option<Hemisphere>,
+    /// Amount of days every season starts earlier, negative values make them
+    /// start later.
+    #[def(0)]
+    offset_days: Option<i64>,
+}
+
+/// Local calendar of a world, relative to the days of the [`crate::time::GameDate`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Resource, Default, Config)]
+#[validate(lengths_are_positive)]
+pub struct CalendarConfig {
+    /// Length of a local day in days of the game date, a sol on Mars would
+    /// be `1.0275`. Has to be positive.
+    #[def(1.)]
+    day_length: Option<f64>,
+    /// Amount of local days in a local year.
+    #[def(365.25)]
+    year_length: Option<f64>,
 }
 
-#[derive(Deserialize, Debug, Clone, Resource, Default, Config)]
+fn lengths_are_positive(config: &CalendarConfig) -> Result<(), String> {
+    match config.day_length() > 0. && config.year_length() > 0. {
+        true => Ok(()),
+        false => Err("`day_length` and `year_length` have to be positive".to_string()),
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Hemisphere {
+    #[default]
+    Northern,
+    Southern,
+}
+
+impl DescribeConfig for Hemisphere {
+    fn shape() -> ConfigShape {
+        ConfigShape::Choice(vec!["northern", "southern"])
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Resource, Default, Config)]
 pub struct SpriteConfig {
     sprite_sheet: String,
     frames: usize,
@@ -103,3 +421,167 @@ pub struct SpriteConfig {
 }
 
 
+impl ConfigurationLoader for ScenarioConfig {
+    fn path_with_name() -> &'static str {
+        "scenario"
+    }
+
+    fn default_if_missing() -> Option<Self> {
+        Some(Self::def_conf())
+    }
+}
+
+/// Shocks that are applied to the worlds on given dates, see
+/// [`crate::worlds::scenario`].
+#[derive(Serialize, Deserialize, Debug, Clone, Resource, ConfigFile, Default, Config)]
+pub struct ScenarioConfig {
+    #[def(Vec::new())]
+    actions: Option<Vec<ScenarioAction>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ScenarioAction {
+    /// Day on which the action is applied, formatted like `2160-01-01`.
+    pub date: NaiveDate,
+    /// Name of the world the action is applied to, every world if left out.
+    #[serde(default)]
+    pub world: Option<String>,
+    #[serde(flatten)]
+    pub kind: ScenarioActionKind,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum ScenarioActionKind {
+    /// Destroys a share of a food resource.
+    Famine {
+        resource: FamineResource,
+        /// Share of the stock that is lost, between 0 and 1.
+        share: f32,
+    },
+    /// Every citizen has an extra chance of dying each day, for the given
+    /// amount of days.
+    Plague {
+        daily_death_chance: f32,
+        days: u64,
+    },
+    /// Replaces parts of the spending policy of the government, anything
+    /// left out stays as it is.
+    PolicySwitch(PolicySwitch),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FamineResource {
+    Carb,
+    Meat,
+    Food,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct PolicySwitch {
+    pub citizen_payout: Option<f32>,
+    pub civil_spending: Option<usize>,
+    pub sanitation_spending: Option<usize>,
+    pub social_spending: Option<usize>,
+    pub environmental_spending: Option<usize>,
+}
+
+impl From<GovernmentConfig> for PolicySwitch {
+    /// Switches to every part of the government's policy.
+    fn from(government: GovernmentConfig) -> Self {
+        Self {
+            citizen_payout: Some(government.citizen_payout()),
+            civil_spending: Some(government.civil_spending()),
+            sanitation_spending: Some(government.sanitation_spending()),
+            social_spending: Some(government.social_spending()),
+            environmental_spending: Some(government.environmental_spending()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_lets_the_world_win() {
+        let mut base = json!({
+            "population": { "size": 1000, "scale": 6.0 },
+            "sprite": { "frames_layout": [60, 1] },
+            "world_position": [0, 0],
+        });
+        merge(&mut base, json!({
+            "population": { "size": 50 },
+            "sprite": { "frames_layout": [120] },
+            "name": "Mars",
+        }));
+        assert_eq!(base, json!({
+            "population": { "size": 50, "scale": 6.0 },
+            "sprite": { "frames_layout": [120] },
+            "world_position": [0, 0],
+            "name": "Mars",
+        }));
+    }
+
+    #[test]
+    fn templates_are_merged_over_every_level() {
+        let mut file = json!({
+            "templates": {
+                "planet": { "population": { "size": 1000, "scale": 6.0 }, "sprite": { "frames": 60 } },
+                "colony": { "extends": "planet", "population": { "size": 500 } },
+            },
+            "worlds": [
+                { "name": "Mars", "extends": "colony", "population": { "shape": 2.0 } },
+                { "name": "Earth", "population": { "size": 10 } },
+            ],
+        });
+        resolve_templates(&mut file).unwrap();
+        assert_eq!(file["worlds"], json!([
+            {
+                "name": "Mars",
+                "extends": "colony",
+                "population": { "size": 500, "scale": 6.0, "shape": 2.0 },
+                "sprite": { "frames": 60 },
+            },
+            { "name": "Earth", "population": { "size": 10 } },
+        ]));
+    }
+
+    #[test]
+    fn templates_extending_each_other_in_a_circle_are_reported_once() {
+        let mut file = json!({
+            "templates": {
+                "a": { "extends": "b" },
+                "b": { "extends": "a" },
+            },
+            "worlds": [
+                { "name": "Mars", "extends": "a" },
+                { "name": "Earth", "extends": "a" },
+            ],
+        });
+        let violations = resolve_templates(&mut file).unwrap_err();
+        assert_eq!(violations, vec![ConfigViolation {
+            path: "templates.b.extends".to_string(),
+            message: "the templates extend each other in a circle through `a`".to_string(),
+        }]);
+    }
+
+    #[test]
+    fn missing_templates_and_unknown_fields_are_reported() {
+        let mut file = json!({
+            "templates": { "colony": { "populaton": {} } },
+            "worlds": [{ "name": "Mars" }],
+        });
+        let violations = resolve_templates(&mut file).unwrap_err();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].path, "templates.colony.populaton");
+
+        let mut file = json!({ "worlds": [{ "name": "Mars", "extends": "colony" }] });
+        let violations = resolve_templates(&mut file).unwrap_err();
+        assert_eq!(violations, vec![ConfigViolation {
+            path: "worlds[0].extends".to_string(),
+            message: "there is no template named `colony`".to_string(),
+        }]);
+    }
+}
@@ -1,7 +1,7 @@
 
 use bevy::prelude::*;
 
-use crate::{SimulationState, worlds::wealth::components::{SpendingPolicy, WealthAndSpending}};
+use crate::{time::DailyTick, worlds::{wealth::{calculate_wealth, components::{SpendingPolicy, WealthAndSpending}}, SimulationSet}};
 
 use super::components::SanitationInfrastructure;
 
@@ -11,10 +11,10 @@ impl Plugin for SanitationInfrastructurePlugin {
     fn build(&self, app: &mut App) {
         app
             .add_systems(
-                Update,
+                DailyTick,
                 (
                     update_sanitation_info
-                ).run_if(in_state(SimulationState::Running))
+                ).after(calculate_wealth).in_set(SimulationSet::Economy)
             );
     }
 }
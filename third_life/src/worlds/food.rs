@@ -9,39 +9,41 @@ use crate::worlds::food::{cow_farming::*, wheat_farming::*};
 
 use std::usize;
 
-use crate::{time::DateChanged, SimulationState};
+use crate::{time::DailyTick, SimulationState};
 use bevy::ecs::world;
 use bevy::{prelude::*, reflect::List, utils::HashMap};
 use bevy_egui::{egui::Window, EguiContexts};
 use chrono::Months;
-use rand_distr::num_traits::Float;
 
 use super::config::WorldConfig;
-use super::{init_colonies, population::components::CitizenOf, WorldColony};
+use super::{init_colonies, population::components::CitizenOf, snapshot::LoadedSnapshot, SimulationSet, WorldColony};
 
 pub struct FoodPlugin;
 impl Plugin for FoodPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
             OnEnter(SimulationState::Running),
-            init_food.after(init_colonies),
+            init_food
+                .after(init_colonies)
+                .run_if(not(resource_exists::<LoadedSnapshot>)),
         )
         .add_systems(
-            Update,
+            DailyTick,
             (
                 season_check_wheat,
-                mark_breeders,
-                breed_cows,
                 check_farm_workers,
                 get_farm_workers,
                 work_farm,
+                mark_breeders,
+                breed_cows,
                 check_cow_farm_workers,
                 get_cow_farm_workers,
                 work_cow_farm,
-                cook_food,
             )
-                .run_if(in_state(SimulationState::Running)),
+                .chain()
+                .in_set(SimulationSet::Production),
         )
+        .add_systems(DailyTick, cook_food.in_set(SimulationSet::Processing))
         .add_event::<WheatFarmNeedsWorker>()
         .add_event::<CowFarmNeedsWorker>()
         .add_event::<CarbCreated>()
@@ -61,8 +63,8 @@ fn init_food(
         for _ in 0..world_config.food().wheat_farms() {
             wheat_farms.push((
                 WheatFarm {
-                    size: 17.4,
-                    harvested: 17.4,
+                    size: world_config.food().wheat_farm_size(),
+                    harvested: world_config.food().wheat_farm_size(),
                 },
                 WheatFarmOf {
                     colony: colony_entity,
@@ -74,7 +76,7 @@ fn init_food(
         for _ in 0..world_config.food().cow_farms() {
             let cow_farm_entity = commands
                 .spawn((
-                    CowFarm { size: 34.0 },
+                    CowFarm { size: world_config.food().cow_farm_size() },
                     CowFarmOf {
                         colony: colony_entity,
                     },
@@ -82,8 +84,9 @@ fn init_food(
                 .id();
             let mut cows = Vec::new();
             let mut bulls = Vec::new();
-            //47 is min starting cows and we want to have 10 ready to harvest right away
-            let total_cows = 57.0;
+            // INFO: By default 10 more than the `min_cows`, so some are
+            // ready to harvest right away.
+            let total_cows = world_config.food().starting_cows() as f32;
             let total_bulls = (total_cows / 25.0).ceil() as usize;
             for _ in 0..total_bulls {
                 bulls.push((
@@ -137,6 +140,7 @@ fn init_food(
 }
 
 fn cook_food(
+    colonies: Query<&WorldConfig>,
     mut food_resources: Query<(Entity, &mut FoodResource, &ResourceOf)>,
     mut carb_resources: Query<(Entity, &mut CarbResource, &ResourceOf)>,
     mut meat_resources: Query<(Entity, &mut MeatResource, &ResourceOf)>,
@@ -182,7 +186,7 @@ fn cook_food(
             .get_mut(*resource_entities.get("meat").unwrap())
             .unwrap();
 
-        let food_cook_multiplier = 5.0;
+        let food_cook_multiplier = colonies.get(colony).unwrap().food().cook_multiplier();
 
         if carb_resource.amount > 100.0 * food_cook_multiplier
             && meat_resource.amount > 100.0 * food_cook_multiplier
@@ -1,22 +1,23 @@
 use bevy::prelude::*;
 use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
 
 #[derive(Component, PartialEq, Eq, Hash)]
 pub struct ResourceOf {
     pub colony: Entity,
 }
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize)]
 pub struct FoodResource {
     pub amount: f32,
 }
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize)]
 pub struct CarbResource {
     pub amount: f32,
 }
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize)]
 pub struct WheatFarm {
     pub size: f32,
     pub harvested: f32,
@@ -32,7 +33,7 @@ pub struct WheatFarmer {
     pub farm: Entity,
 }
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize)]
 pub struct Cow {
     pub birthday: NaiveDate
 }
@@ -48,12 +49,12 @@ pub struct IsBull;
 #[derive(Component)]
 pub struct IsBreeder;
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize)]
 pub struct MeatResource {
     pub amount: f32,
 }
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize)]
 pub struct CowFarm {
     pub size: f32,
 }
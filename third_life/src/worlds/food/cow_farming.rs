@@ -1,13 +1,14 @@
-use std::usize;
+use std::{collections::{BTreeMap, BTreeSet}, usize};
 
-use bevy::{prelude::*, reflect::List, transform::commands, utils::hashbrown::HashMap};
+use bevy::{prelude::*, reflect::List, transform::commands};
 use chrono::{Datelike, NaiveDate};
 use rand_distr::num_traits::Float;
 
 use crate::{
     common::utils::roll_chance,
-    time::{DateChanged, GameDate},
-    worlds::population::components::{CitizenOf, Employed, Retiree, Youngling},
+    rng::SimRng,
+    time::{GameDate, Season},
+    worlds::{calendar::LocalDayChanged, config::WorldConfig, population::components::{CitizenOf, Employed, Retiree, Youngling}, seasons::WorldSeasonChanged},
 };
 
 use super::{
@@ -22,17 +23,17 @@ pub fn mark_breeders(
     bulls: Query<(Entity, &CowOf), (With<IsBull>, Without<IsBreeder>)>,
 ) {
     let mut farms_map = cow_farms.iter().fold(
-        HashMap::new(),
-        |mut acc: HashMap<Entity, HashMap<&str, Vec<Entity>>>, farm_entity| {
+        BTreeMap::new(),
+        |mut acc: BTreeMap<Entity, BTreeMap<&str, Vec<Entity>>>, farm_entity| {
             acc.entry(farm_entity)
-                .or_insert(HashMap::new())
+                .or_default()
                 .entry("breeders")
-                .or_insert(Vec::new());
+                .or_default();
 
             acc.entry(farm_entity)
-                .or_insert(HashMap::new())
+                .or_default()
                 .entry("bulls")
-                .or_insert(Vec::new());
+                .or_default();
             acc
         },
     );
@@ -67,57 +68,69 @@ pub fn mark_breeders(
     }
 }
 
+/// Season in which the cows of a world have their calves.
+const BREEDING_SEASON: Season = Season::Summer;
+
 pub fn breed_cows(
     mut commands: Commands,
-    mut day_changed_event_reader: EventReader<DateChanged>,
+    mut season_changed: EventReader<WorldSeasonChanged>,
     cows: Query<(&Cow, &CowOf), Without<IsBull>>,
+    cow_farms: Query<&CowFarmOf>,
+    mut sim_rng: ResMut<SimRng>,
+    game_date: Res<GameDate>,
 ) {
-    for day in day_changed_event_reader.read() {
-        if day.date.month() == 6 && day.date.day() == 1 {
-            let mut cows_to_spawn: Vec<_> = Vec::new();
-            let mut bulls_to_spawn: Vec<_> = Vec::new();
-            for (_, cow_of) in cows.iter() {
-                match roll_chance(50) {
-                    true => cows_to_spawn.push((
-                        Cow { birthday: day.date },
-                        CowOf {
-                            cow_farm: cow_of.cow_farm,
-                        },
-                    )),
-                    false => bulls_to_spawn.push((
-                        Cow { birthday: day.date },
-                        CowOf {
-                            cow_farm: cow_of.cow_farm,
-                        },
-                        IsBull,
-                    )),
-                }
+    for changed in season_changed.read().filter(|c| c.season == BREEDING_SEASON) {
+        let mut cows_to_spawn: Vec<_> = Vec::new();
+        let mut bulls_to_spawn: Vec<_> = Vec::new();
+        for (_, cow_of) in cows.iter() {
+            let colony = cow_farms.get(cow_of.cow_farm).unwrap().colony;
+            if colony != changed.colony {
+                continue;
+            }
+            match roll_chance(50, sim_rng.colony(colony)) {
+                true => cows_to_spawn.push((
+                    Cow { birthday: game_date.date },
+                    CowOf {
+                        cow_farm: cow_of.cow_farm,
+                    },
+                )),
+                false => bulls_to_spawn.push((
+                    Cow { birthday: game_date.date },
+                    CowOf {
+                        cow_farm: cow_of.cow_farm,
+                    },
+                    IsBull,
+                )),
             }
-            commands.spawn_batch(cows_to_spawn);
-            commands.spawn_batch(bulls_to_spawn);
         }
+        commands.spawn_batch(cows_to_spawn);
+        commands.spawn_batch(bulls_to_spawn);
     }
 }
 
 pub fn check_cow_farm_workers(
-    mut day_changed_event_reader: EventReader<DateChanged>,
+    mut local_days: EventReader<LocalDayChanged>,
     mut event_writer: EventWriter<CowFarmNeedsWorker>,
+    colonies: Query<&WorldConfig>,
     cow_farms: Query<(Entity, &CowFarmOf), With<CowFarm>>,
     farmers: Query<(&CowFarmer, &CitizenOf)>,
 ) {
-    for _ in day_changed_event_reader.read() {
-        let mut farms_map = cow_farms.iter().fold(
-            HashMap::new(),
-            |mut acc: HashMap<Entity, HashMap<Entity, usize>>, (farm_entity, cow_farm_of)| {
+    // INFO: The new workers are only hired after this system ran, so a colony
+    // with more than one local day on this game day is checked only once.
+    let days = local_days.read().map(|day| day.colony).collect::<BTreeSet<_>>();
+    if !days.is_empty() {
+        let mut farms_map = cow_farms.iter().filter(|f| days.contains(&f.1.colony)).fold(
+            BTreeMap::new(),
+            |mut acc: BTreeMap<Entity, BTreeMap<Entity, usize>>, (farm_entity, cow_farm_of)| {
                 acc.entry(cow_farm_of.colony)
-                    .or_insert(HashMap::new())
+                    .or_default()
                     .entry(farm_entity)
                     .or_insert(0);
                 acc
             },
         );
 
-        for (cow_farmer, colony_of) in farmers.iter() {
+        for (cow_farmer, colony_of) in farmers.iter().filter(|f| days.contains(&f.1.colony)) {
             farms_map
                 .get_mut(&colony_of.colony)
                 .unwrap()
@@ -126,9 +139,10 @@ pub fn check_cow_farm_workers(
         }
 
         for (colony, farms) in farms_map {
+            let workers = colonies.get(colony).unwrap().food().cow_farm_workers();
             for (farm, farmer_count) in farms {
-                if farmer_count < 4 {
-                    for _ in 0..(4 - farmer_count) {
+                if farmer_count < workers {
+                    for _ in 0..(workers - farmer_count) {
                         event_writer.send(CowFarmNeedsWorker { colony, farm });
                     }
                 }
@@ -159,41 +173,47 @@ pub fn get_cow_farm_workers(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn work_cow_farm(
     mut commands: Commands,
     game_date: Res<GameDate>,
-    mut day_changed_event_reader: EventReader<DateChanged>,
+    mut local_days: EventReader<LocalDayChanged>,
+    colonies: Query<&WorldConfig>,
     mut cow_farms: Query<(Entity, &mut CowFarm, &CowFarmOf)>,
     cows: Query<(Entity, &Cow, &CowOf)>,
     bulls: Query<(Entity, &Cow, &CowOf), (With<IsBull>, Without<IsBreeder>)>,
     farmers: Query<(&CowFarmer, &CitizenOf)>,
     mut meat_resources: Query<(&mut MeatResource, &ResourceOf)>,
 ) {
-    for _ in day_changed_event_reader.read() {
-        let mut farms_map = cow_farms.iter_mut().fold(
-            HashMap::new(),
-            |mut acc: HashMap<
+    // INFO: Cows are only despawned after this system ran, the ones that
+    // were already slaughtered on an earlier local day of the same game day
+    // have to be skipped.
+    let mut slaughtered = BTreeSet::new();
+    for day in local_days.read() {
+        let mut farms_map = cow_farms.iter_mut().filter(|f| f.2.colony == day.colony).fold(
+            BTreeMap::new(),
+            |mut acc: BTreeMap<
                 Entity,
-                HashMap<Entity, Vec<(Entity, &Cow)>>,
+                BTreeMap<Entity, Vec<(Entity, &Cow)>>,
             >,
              (farm_entity, _, cow_farm_of)| {
                 acc.entry(cow_farm_of.colony)
-                    .or_insert(HashMap::new())
+                    .or_default()
                     .entry(farm_entity)
-                    .or_insert(Vec::new());
+                    .or_default();
                 acc
             },
         );
         
         for (_, farms) in farms_map.iter_mut() {
-            for (bull_entity, cow, cow_of) in bulls.iter() {
+            for (bull_entity, cow, cow_of) in bulls.iter().filter(|b| !slaughtered.contains(&b.0)) {
                 farms
                     .entry(cow_of.cow_farm)
                     .and_modify(|f| f.push((bull_entity, cow)));
             }
         }
         for (_, farms) in farms_map.iter_mut() {
-            for (cow_entity, cow, cow_of) in cows.iter() {
+            for (cow_entity, cow, cow_of) in cows.iter().filter(|c| !slaughtered.contains(&c.0)) {
                 farms
                     .entry(cow_of.cow_farm)
                     .and_modify(|f| f.push((cow_entity, cow)));
@@ -201,28 +221,27 @@ pub fn work_cow_farm(
         }
 
         let farmers_map = farmers.iter().fold(
-            HashMap::new(),
-            |mut acc: HashMap<Entity, usize>, (cow_farmer, _)| {
+            BTreeMap::new(),
+            |mut acc: BTreeMap<Entity, usize>, (cow_farmer, _)| {
                 *acc.entry(cow_farmer.farm).or_insert(0) += 1;
                 acc
             },
         );
 
         for (colony, farms) in farms_map {
-            let mut meat_harvested = 0;
+            let food = colonies.get(colony).unwrap().food();
+            let mut meat_harvested = 0.;
             for (farm_entity, cows) in farms {
                 let cows_count = cows.len();
-                //47 is the min amount of cows we want to keep
-                let min_to_keep = 47;
+                let min_to_keep = food.min_cows();
                 if cows_count <= min_to_keep {
                     continue;
                 }
-                let mut to_harvest = cows_count - 47;
-                if to_harvest as f32
-                    > (farmers_map.get(&farm_entity).unwrap_or(&0) * 8) as f32 / 6.25
-                {
-                    to_harvest = ((farmers_map.get(&farm_entity).unwrap_or(&0) * 8) as f32 / 6.25)
-                        .floor() as usize;
+                let mut to_harvest = cows_count - min_to_keep;
+                let farmer_hours = *farmers_map.get(&farm_entity).unwrap_or(&0) as f32
+                    * food.farmer_hours_per_day();
+                if to_harvest as f32 > farmer_hours / food.hours_per_cow() {
+                    to_harvest = (farmer_hours / food.hours_per_cow()).floor() as usize;
                 }
                 
 
@@ -234,7 +253,8 @@ pub fn work_cow_farm(
                     //TODO: maybe better implement this to kill specific cows by age etc
                     if months > 18 {
                         commands.get_entity(cow.0).map(|mut e| e.despawn());
-                        meat_harvested += 250;
+                        slaughtered.insert(cow.0);
+                        meat_harvested += food.meat_per_cow();
                         to_harvest -= 1;
                     }
                 }
@@ -242,7 +262,7 @@ pub fn work_cow_farm(
 
             for (mut meat_resource, resource_of) in meat_resources.iter_mut() {
                 if resource_of.colony == colony {
-                    meat_resource.amount += meat_harvested as f32;
+                    meat_resource.amount += meat_harvested;
                 }
             }
         }
@@ -1,24 +1,26 @@
-use bevy::{prelude::*, utils::HashMap};
-use chrono::{Datelike, NaiveDate};
+use std::collections::BTreeMap;
 
-use crate::{time::{DateChanged, GameDate}, worlds::population::components::{CitizenOf, Employed}};
+use bevy::prelude::*;
+
+use crate::{time::{DateChanged, GameDate, Season}, worlds::{calendar::LocalDayChanged, config::WorldConfig, population::components::{CitizenOf, Employed}, seasons::WorldSeasonChanged}};
 
 use super::{
     CarbCreated, CarbResource, ResourceOf, WheatFarm, WheatFarmNeedsWorker, WheatFarmOf,
     WheatFarmer,
 };
 
+/// Season in which the farms of a world can be harvested again.
+const HARVEST_SEASON: Season = Season::Summer;
+
 pub fn season_check_wheat(
-    mut day_changed_event_reader: EventReader<DateChanged>,
-    mut wheat_farms: Query<&mut WheatFarm>,
+    mut season_changed: EventReader<WorldSeasonChanged>,
+    mut wheat_farms: Query<(&mut WheatFarm, &WheatFarmOf)>,
     game_date: Res<GameDate>
 ) {
-    for _ in day_changed_event_reader.read() {
-        if game_date.date.month() == 6 && game_date.date.day() == 1 {
-            warn!("Harvest season has begun {:?}", game_date.date);
-            for mut wheat_farm in wheat_farms.iter_mut() {
-                wheat_farm.harvested = 0.0;
-            }
+    for changed in season_changed.read().filter(|c| c.season == HARVEST_SEASON) {
+        warn!("Harvest season has begun {:?}", game_date.date);
+        for (mut wheat_farm, _) in wheat_farms.iter_mut().filter(|(_, of)| of.colony == changed.colony) {
+            wheat_farm.harvested = 0.0;
         }
     }
 }
@@ -26,15 +28,16 @@ pub fn season_check_wheat(
 pub fn check_farm_workers(
     mut day_changed_event_reader: EventReader<DateChanged>,
     mut event_writer: EventWriter<WheatFarmNeedsWorker>,
+    colonies: Query<&WorldConfig>,
     wheat_farms: Query<(Entity, &WheatFarmOf), With<WheatFarm>>,
     farmers: Query<(&WheatFarmer, &CitizenOf)>,
 ) {
     for _ in day_changed_event_reader.read() {
         let mut farms_map = wheat_farms.iter().fold(
-            HashMap::new(),
-            |mut acc: HashMap<Entity, HashMap<Entity, usize>>, (farm_entity, wheat_farm_of)| {
+            BTreeMap::new(),
+            |mut acc: BTreeMap<Entity, BTreeMap<Entity, usize>>, (farm_entity, wheat_farm_of)| {
                 acc.entry(wheat_farm_of.colony)
-                    .or_insert(HashMap::new())
+                    .or_default()
                     .entry(farm_entity)
                     .or_insert(0);
                 acc
@@ -50,9 +53,10 @@ pub fn check_farm_workers(
         }
 
         for (colony, farms) in farms_map {
+            let workers = colonies.get(colony).unwrap().food().wheat_farm_workers();
             for (farm, farmer_count) in farms {
-                if farmer_count < 4 {
-                    for _ in 0..(4 - farmer_count) {
+                if farmer_count < workers {
+                    for _ in 0..(workers - farmer_count) {
                         event_writer.send(WheatFarmNeedsWorker { colony, farm });
                     }
                 }
@@ -84,25 +88,26 @@ pub fn get_farm_workers(
 }
 
 pub fn work_farm(
-    mut day_changed_event_reader: EventReader<DateChanged>,
+    mut local_days: EventReader<LocalDayChanged>,
+    colonies: Query<&WorldConfig>,
     mut wheat_farms: Query<(Entity, &mut WheatFarm, &WheatFarmOf)>,
     farmers: Query<(&WheatFarmer, &CitizenOf)>,
     mut carb_resources: Query<(&mut CarbResource, &ResourceOf)>,
     mut carb_created: EventWriter<CarbCreated>,
 ) {
-    for _ in day_changed_event_reader.read() {
-        let mut farms_map = wheat_farms.iter_mut().fold(
-            HashMap::new(),
-            |mut acc: HashMap<Entity, HashMap<Entity, usize>>, (farm_entity, _, wheat_farm_of)| {
+    for day in local_days.read() {
+        let mut farms_map = wheat_farms.iter_mut().filter(|f| f.2.colony == day.colony).fold(
+            BTreeMap::new(),
+            |mut acc: BTreeMap<Entity, BTreeMap<Entity, usize>>, (farm_entity, _, wheat_farm_of)| {
                 acc.entry(wheat_farm_of.colony)
-                    .or_insert(HashMap::new())
+                    .or_default()
                     .entry(farm_entity)
                     .or_insert(0);
                 acc
             },
         );
 
-        for (wheat_farmer, colony_of) in farmers.iter() {
+        for (wheat_farmer, colony_of) in farmers.iter().filter(|f| f.1.colony == day.colony) {
             farms_map
                 .get_mut(&colony_of.colony)
                 .unwrap()
@@ -111,6 +116,7 @@ pub fn work_farm(
         }
 
         for (colony, farms) in farms_map {
+            let carbs_per_hectare = colonies.get(colony).unwrap().food().carbs_per_hectare();
             for (farm_entity, farmer_count) in farms {
                 let (_, mut wheat_farm, _) = wheat_farms.get_mut(farm_entity).unwrap();
                 // 1.0 signifies multiplier for 1 8 hour work day
@@ -123,7 +129,7 @@ pub fn work_farm(
                 if harvested_amount > 0.0 {
                     for (mut carb_resource, resource_of) in carb_resources.iter_mut() {
                         if resource_of.colony == colony {
-                            let amount = harvested_amount * 2670.0;
+                            let amount = harvested_amount * carbs_per_hectare;
                             carb_resource.amount += amount;
                             carb_created.send(CarbCreated { colony, amount });
                         }
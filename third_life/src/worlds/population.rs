@@ -12,14 +12,15 @@ use giving_birth::*;
 use relationships::*;
 
 use crate::{
-    common::utils::roll_chance,
-    time::{DateChanged, GameDate},
+    common::utils::{generate_name, roll_chance},
+    rng::{init_sim_rng, SimRng},
+    time::{DailyTick, DateChanged, GameDate},
     SimulationState,
 };
 use bevy::{prelude::*, utils::HashMap};
 use bevy_egui::egui::ahash::HashMapExt;
 use chrono::{Datelike, NaiveDate};
-use rand::{thread_rng, Rng};
+use rand::Rng;
 use rand_distr::{
     num_traits::{real::Real, Float},
     Distribution, SkewNormal,
@@ -29,8 +30,8 @@ use rnglib::{Language, RNG};
 use self::food_consumption::FoodConsumptionPlugin;
 
 use super::{
-    config::{WorldConfig, WorldsConfig},
-    init_colonies, WorldColony, WorldEntity,
+    config::{PopulationConfig, WorldConfig, WorldsConfig},
+    init_colonies, snapshot::LoadedSnapshot, SimulationSet, WorldColony, WorldEntity,
 };
 
 pub struct PopulationPlugin;
@@ -39,13 +40,20 @@ impl Plugin for PopulationPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
             OnEnter(SimulationState::Running),
-            (init_citizens).chain().after(init_colonies),
+            (init_citizens)
+                .chain()
+                .after(init_colonies)
+                .after(init_sim_rng)
+                .run_if(not(resource_exists::<LoadedSnapshot>)),
         )
         .add_systems(
-            Update,
-            (update_population, check_birthdays, come_of_age, retirement)
-                .run_if(in_state(SimulationState::Running)),
+            DailyTick,
+            (check_birthdays, come_of_age, retirement)
+                .chain()
+                .before(init_ovulation)
+                .in_set(SimulationSet::Lifecycle),
         )
+        .add_systems(DailyTick, update_population.in_set(SimulationSet::Statistics))
         .add_event::<CitizenBirthday>()
         .add_plugins((
             GivingBirthPlugin,
@@ -60,11 +68,12 @@ pub fn init_citizens(
     colonies: Query<(Entity, &WorldConfig), With<WorldColony>>,
     mut commands: Commands,
     mut event_writer: EventWriter<CitizenCreated>,
+    mut sim_rng: ResMut<SimRng>,
     game_date: Res<GameDate>,
 ) {
     for (colony, pop_config) in colonies.iter() {
         let pop_config = pop_config.population();
-        let mut rng = thread_rng();
+        let rng = sim_rng.colony(colony);
         let name_rng = RNG::try_from(&Language::Roman).unwrap();
         let skew_normal = SkewNormal::new(
             pop_config.location(),
@@ -72,23 +81,22 @@ pub fn init_citizens(
             pop_config.shape(),
         )
         .unwrap();
-        let mut age_gen = skew_normal.sample_iter(&mut rng);
         let year = game_date.date.year_ce().1 as usize;
 
         for _ in 0..pop_config.size() {
-            let age = age_gen.next().unwrap().floor() as usize;
+            let age = skew_normal.sample(rng).floor() as usize;
             let birthday = NaiveDate::from_yo_opt(
                 (year - age).try_into().unwrap(),
-                thread_rng().gen_range(1..=365),
+                rng.gen_range(1..=365),
             )
             .unwrap();
 
             let citizen = Citizen {
-                name: name_rng.generate_name(),
+                name: generate_name(&name_rng, rng),
                 birthday,
             };
-            if game_date.years_since(birthday).unwrap() >= 18 as u32 {
-                match roll_chance(50) {
+            if game_date.years_since(birthday).unwrap() >= pop_config.adult_age() {
+                match roll_chance(50, rng) {
                     true => commands.spawn((citizen, Employable, CitizenOf { colony }, Male)),
                     false => commands.spawn((
                         citizen,
@@ -98,7 +106,7 @@ pub fn init_citizens(
                     )),
                 };
             } else {
-                match roll_chance(50) {
+                match roll_chance(50, rng) {
                     true => commands.spawn((citizen, CitizenOf { colony }, Male)),
                     false => {
                         commands.spawn((citizen, CitizenOf { colony }, Female { children_had: 0 }))
@@ -181,12 +189,27 @@ pub fn check_birthdays(
     }
 }
 
+/// Age at which the citizen of a [`CitizenBirthday`] reaches `threshold`
+/// in its colony, `None` if the citizen is gone by now.
+fn colony_age(
+    birthday: &CitizenBirthday,
+    citizens: &Query<&CitizenOf>,
+    colonies: &Query<&WorldConfig>,
+    threshold: impl Fn(&PopulationConfig) -> u32,
+) -> Option<usize> {
+    let colony = citizens.get(birthday.entity).ok()?.colony;
+    Some(threshold(&colonies.get(colony).ok()?.population()) as usize)
+}
+
 pub fn come_of_age(
     mut commands: Commands,
     mut birthday_event_reader: EventReader<CitizenBirthday>,
+    citizens: Query<&CitizenOf>,
+    colonies: Query<&WorldConfig>,
 ) {
     for birthday in birthday_event_reader.read() {
-        if birthday.age == 18 {
+        let adult_age = colony_age(birthday, &citizens, &colonies, PopulationConfig::adult_age);
+        if Some(birthday.age) == adult_age {
             commands.get_entity(birthday.entity).map(|mut e| {
                 e.remove::<Youngling>();
             });
@@ -195,9 +218,15 @@ pub fn come_of_age(
     }
 }
 
-pub fn retirement(mut commands: Commands, mut birthday_event_reader: EventReader<CitizenBirthday>) {
+pub fn retirement(
+    mut commands: Commands,
+    mut birthday_event_reader: EventReader<CitizenBirthday>,
+    citizens: Query<&CitizenOf>,
+    colonies: Query<&WorldConfig>,
+) {
     for birthday in birthday_event_reader.read() {
-        if birthday.age == 65 {
+        let retirement_age = colony_age(birthday, &citizens, &colonies, PopulationConfig::retirement_age);
+        if Some(birthday.age) == retirement_age {
             commands.get_entity(birthday.entity).map(|mut e| {
                 e.remove::<Youngling>();
             });
@@ -1,17 +1,20 @@
 use bevy::prelude::*;
 use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
 
-#[derive(Component, Default)]
+#[derive(Component, Default, Serialize, Deserialize)]
 pub struct Population {
     pub count: usize,
     pub working_pop: usize,
     pub younglings: usize,
     pub retirees: usize,
     pub average_age: usize,
+    /// `NaN` as long as there are no women in the colony.
+    #[serde(with = "crate::common::utils::nan_as_null")]
     pub average_children_per_mother: f32
 }
 
-#[derive(Component, PartialEq, Clone)]
+#[derive(Component, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Citizen {
     pub name: String,
     pub birthday: NaiveDate,
@@ -22,16 +25,27 @@ pub struct CitizenOf {
     pub colony: Entity,
 }
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize)]
 pub struct Starving {
     pub days_since_last_meal: usize
 }
 
 impl Starving {
-    pub fn died(&self) -> bool { self.days_since_last_meal > 21 }
+    /// Whether the citizen went without a meal for longer than the
+    /// `starvation_days` of its colony.
+    pub fn died(&self, starvation_days: usize) -> bool { self.days_since_last_meal > starvation_days }
 }
 
-#[derive(Component)]
+/// Put on a colony while a plague, started by a scenario action, is going
+/// on. Every citizen of the colony has an extra chance of dying each day.
+#[derive(Component, Serialize, Deserialize)]
+pub struct Plague {
+    pub daily_death_chance: f32,
+    /// Last day of the plague.
+    pub until: NaiveDate,
+}
+
+#[derive(Component, Serialize, Deserialize)]
 pub struct Female {
     pub children_had: usize
 }
@@ -39,12 +53,12 @@ pub struct Female {
 #[derive(Component)]
 pub struct Male;
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize)]
 pub struct Ovulation {
     pub ovulation_start_date: NaiveDate,
 }
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize)]
 pub struct Pregnancy {
     pub baby_due_date: NaiveDate,
 }
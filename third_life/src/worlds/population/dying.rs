@@ -1,8 +1,8 @@
-use std::{f32::consts, iter::zip, collections::HashMap};
+use std::{f32::consts, collections::{BTreeMap, HashMap}};
 use crate::{
-    common::utils::roll_chance, time::{DateChanged, GameDate, MonthChanged}, worlds::{config::{WorldConfig, WorldsConfig}, env_and_infra::components::SanitationInfrastructure, WorldEntity}, SimulationState
+    common::utils::roll_chance, rng::SimRng, time::{DailyTick, DateChanged, GameDate, MonthChanged}, worlds::{config::{WorldConfig, WorldsConfig}, env_and_infra::components::SanitationInfrastructure, SimulationSet, WorldEntity}
 };
-use super::{events::*, components::*};
+use super::{events::*, components::*, giving_birth::citizen_births};
 use bevy::{prelude::*, transform::commands};
 use bevy_egui::{egui::{Window}, EguiContexts};
 use chrono::{Datelike, NaiveDate, Days};
@@ -16,11 +16,12 @@ impl Plugin for DeathsPlugin {
     fn build(&self, app: &mut App) {
         app
             .add_systems(
-                Update,(
+                DailyTick,(
                     old_age_death,
                     starvation,
+                    plague_deaths,
                     infant_mortality
-                ).run_if(in_state(SimulationState::Running))
+                ).chain().after(citizen_births).in_set(SimulationSet::Lifecycle)
             )
             .add_event::<CitizenDied>();
     }
@@ -32,6 +33,7 @@ pub fn old_age_death(
     mut commands: Commands,
     citizens: Query<(Entity, &CitizenOf, &Citizen)>,
     game_date: Res<GameDate>,
+    mut sim_rng: ResMut<SimRng>,
     mut death_events: EventWriter<CitizenDied>,
 ) {
     let epi_map = worlds.iter().map(|(e, w)| {
@@ -41,15 +43,16 @@ pub fn old_age_death(
     }).collect::<HashMap<_, _>>();
 
     let days_passed = date_changed.read().collect::<Vec<_>>();
-    let citizens = citizens.iter().collect::<Vec<_>>();
-    let mut rng = rand::thread_rng();
-    let probs = (0..citizens.len()).into_iter().map(|_| rng.gen()).collect::<Vec<f32>>();
 
-    zip(citizens, probs).into_iter().fold(HashMap::new(), |mut acc: HashMap<_, _>, ((entity, colony, citizen), prob)| {
-        acc.entry(colony.colony).or_insert(vec![]).push((entity, citizen, prob)); acc
+    // INFO: BTreeMap so that the colonies, and with them the despawns, are
+    // always handled in the same order. Needed for reproducible runs.
+    citizens.iter().fold(BTreeMap::new(), |mut acc: BTreeMap<_, _>, (entity, colony, citizen)| {
+        acc.entry(colony.colony).or_insert(vec![]).push((entity, citizen)); acc
     }).into_iter().for_each(|(colony, citizens)| {
         let (life_exp, spread) = epi_map.get(&colony).unwrap();
-        citizens.into_iter().for_each(|(e, c, rnd)| {
+        let rng = sim_rng.colony(colony);
+        citizens.into_iter().for_each(|(e, c)| {
+            let rnd = rng.gen::<f32>();
             // INFO: Small optimization. Age is only calculated once meaning that
             // if the birthday is in the days passed we are still using the old
             // age. Since 
@@ -81,9 +84,11 @@ fn starvation(
     mut commands: Commands,
     mut death_events: EventWriter<CitizenDied>,
     starving_citizens: Query<(Entity, &CitizenOf,  &Starving)>,
+    colonies: Query<&WorldConfig>,
 ) {
     for (entity, CitizenOf { colony }, starving) in starving_citizens.iter() {
-        if starving.died() {
+        let starvation_days = colonies.get(*colony).unwrap().population().starvation_days();
+        if starving.died(starvation_days) {
             commands.get_entity(entity).map(|mut e| {
                 e.despawn();
             });
@@ -92,12 +97,53 @@ fn starvation(
     }
 }
 
+fn plague_deaths(
+    mut commands: Commands,
+    mut death_events: EventWriter<CitizenDied>,
+    game_date: Res<GameDate>,
+    mut sim_rng: ResMut<SimRng>,
+    plagues: Query<(Entity, &Plague)>,
+    citizens: Query<(Entity, &CitizenOf)>,
+) {
+    if plagues.is_empty() {
+        return;
+    }
+    let chances = plagues.iter()
+        .filter(|(_, plague)| plague.until >= game_date.date)
+        .map(|(colony, plague)| (colony, plague.daily_death_chance))
+        .collect::<HashMap<_, _>>();
+    for (colony, plague) in &plagues {
+        if plague.until < game_date.date {
+            commands.entity(colony).remove::<Plague>();
+        }
+    }
+
+    // INFO: BTreeMap for the same reason as in `old_age_death`.
+    citizens.iter()
+        .filter(|(_, of)| chances.contains_key(&of.colony))
+        .fold(BTreeMap::new(), |mut acc: BTreeMap<_, Vec<_>>, (entity, of)| {
+            acc.entry(of.colony).or_default().push(entity); acc
+        })
+        .into_iter()
+        .for_each(|(colony, citizens)| {
+            let chance = chances[&colony];
+            let rng = sim_rng.colony(colony);
+            for entity in citizens {
+                if rng.gen::<f32>() < chance {
+                    commands.entity(entity).despawn();
+                    death_events.send(CitizenDied::plague(colony, entity));
+                }
+            }
+        });
+}
+
 pub fn infant_mortality(
     game_date: Res<GameDate>,
     mut new_days: EventReader<DateChanged>,
     mut commands: Commands,
     infra: Query<(Entity, &SanitationInfrastructure)>,
     citizens: Query<(Entity, &CitizenOf, &Citizen)>,
+    mut sim_rng: ResMut<SimRng>,
     mut death_event: EventWriter<CitizenDied>,
 ) {
     let rates = infra.into_iter().map(|e|(e.0, e.1.live_birth_mortality_rate)).collect::<HashMap<_, _>>();
@@ -106,8 +152,6 @@ pub fn infant_mortality(
 
     let first_date = game_date.date - Days::new(events.len() as u64);
 
-    let mut rng = rand::thread_rng();
-
     citizens.into_iter().for_each(|(entity, of, citizen)| {
         if citizen.birthday > game_date.date && citizen.birthday < first_date {
             return;
@@ -115,7 +159,7 @@ pub fn infant_mortality(
         let days_since_birth = (game_date.date - citizen.birthday).num_days() as usize;
 
         if days_since_birth > (365 - events.len()) && days_since_birth <= 365 {
-            let r = rng.gen::<f32>();
+            let r = sim_rng.colony(of.colony).gen::<f32>();
             if r < *rates.get(&of.colony).unwrap() {
                 commands.get_entity(entity).map(|mut e| {
                     e.despawn();
@@ -8,7 +8,7 @@ pub struct CitizenCreated {
 }
 
 pub enum DeathReason {
-    OldAge, Starvation, InfantDeath
+    OldAge, Starvation, InfantDeath, Plague
 }
 
 #[derive(Event)]
@@ -28,6 +28,9 @@ impl CitizenDied {
     pub fn infant_death(colony: Entity, citizen: Entity) -> Self {
         Self { colony, citizen, reason: DeathReason::InfantDeath }
     }
+    pub fn plague(colony: Entity, citizen: Entity) -> Self {
+        Self { colony, citizen, reason: DeathReason::Plague }
+    }
 }
 
 #[derive(Event)]
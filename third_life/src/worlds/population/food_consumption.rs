@@ -1,32 +1,42 @@
 use bevy::{prelude::*, utils::HashMap};
 
-use crate::{time::DateChanged, worlds::food::components::{FoodResource, ResourceOf}, SimulationState};
+use crate::{time::DailyTick, worlds::{calendar::LocalDayChanged, config::WorldConfig, food::components::{FoodResource, ResourceOf}, SimulationSet}};
 
 use super::{Citizen, CitizenOf, Starving};
 
 pub struct FoodConsumptionPlugin;
 impl Plugin for FoodConsumptionPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, (consume).run_if(in_state(SimulationState::Running)));
+        app.add_systems(DailyTick, (consume).in_set(SimulationSet::Consumption));
     }
 }
 
 fn consume(
     mut commands: Commands,
-    mut day_changed_event_reader: EventReader<DateChanged>,
+    mut local_days: EventReader<LocalDayChanged>,
     mut citizens: Query<(Entity, &Citizen, &CitizenOf, Option<&mut Starving>)>,
     mut food_resources: Query<(&mut FoodResource, &ResourceOf)>,
+    colonies: Query<&WorldConfig>,
 ) {
     let mut food_map = food_resources
         .iter_mut()
         .map(|(food_resource, resource_of)| (resource_of.colony, food_resource))
         .collect::<HashMap<_, _>>();
 
-    for _ in day_changed_event_reader.read() {
-        citizens.iter_mut().fold(
+    let days = local_days.read().fold(HashMap::new(), |mut acc: HashMap<Entity, usize>, day| {
+        *acc.entry(day.colony).or_insert(0) += 1;
+        acc
+    });
+    // INFO: Colonies with short days can have more than one local day on the
+    // same game day. All colonies that have an n-th day eat together, so the
+    // order in which the citizens are handled stays the same as long as every
+    // colony has a single day.
+    for round in 0..days.values().max().copied().unwrap_or(0) {
+        citizens.iter_mut().filter(|c| days.get(&c.2.colony).is_some_and(|d| *d > round)).fold(
             HashMap::new(),
             |mut acc: HashMap<Entity, f32>, (entity, _, citizen_of, starving)| {
-                let food_eaten = 1.0 * 1.0;
+                let food_eaten = colonies.get(citizen_of.colony).unwrap()
+                    .population().daily_food_intake();
                 *acc.entry(citizen_of.colony).or_insert(0.0) += food_eaten;
                 let food_resource = food_map.get_mut(&citizen_of.colony).unwrap();
                 if food_resource.amount < *acc.get(&citizen_of.colony).unwrap() {
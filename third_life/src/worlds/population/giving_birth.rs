@@ -1,13 +1,14 @@
 use std::{f32::consts, iter::zip};
 use crate::{
-    common::utils::roll_chance,
-    time::{DateChanged, GameDate, MonthChanged},
-    SimulationState, worlds::WorldColony,
+    common::utils::{generate_name, roll_chance},
+    rng::SimRng,
+    time::{DailyTick, DateChanged, GameDate, MonthChanged},
+    worlds::{config::WorldConfig, SimulationSet, WorldColony},
 };
 use bevy::{prelude::*, transform::commands};
 use bevy_egui::{egui::{Window, ahash::{HashMap, HashMapExt}}, EguiContexts};
 use chrono::{Datelike, NaiveDate};
-use rand::{thread_rng, Rng, rngs::ThreadRng};
+use rand::Rng;
 use rand_distr::{num_traits::{Float, real::Real}, Distribution, SkewNormal};
 use rnglib::{Language, RNG};
 
@@ -19,13 +20,13 @@ impl Plugin for GivingBirthPlugin {
     fn build(&self, app: &mut App) {
         app
             .add_systems(
-                Update,(
+                DailyTick,(
                     init_ovulation,
                     end_ovulation,
                     init_miscarriage,
                     init_pregnancies,
                     citizen_births,
-                ).run_if(in_state(SimulationState::Running))
+                ).chain().in_set(SimulationSet::Lifecycle)
             )
             .add_event::<CitizenCreated>();
     }
@@ -38,6 +39,7 @@ pub fn citizen_births(
     mut pregnant_women: Query<(Entity, &mut Citizen, &mut Pregnancy, &CitizenOf), With<Pregnancy>>,
     colonies: Query<Entity, With<WorldColony>>,
     game_date: Res<GameDate>,
+    mut sim_rng: ResMut<SimRng>,
 ) {
     for _ in event_reader.read() {
         for (entity, _, pregnancy, citizen_of) in &mut pregnant_women.iter_mut() {
@@ -45,13 +47,14 @@ pub fn citizen_births(
                 for colony in &colonies {
                     if citizen_of.colony == colony {
                         let name_rng = RNG::try_from(&Language::Roman).unwrap();
+                        let rng = sim_rng.colony(colony);
 
                         let new_born = Citizen {
-                            name: name_rng.generate_name(),
+                            name: generate_name(&name_rng, rng),
                             birthday: game_date.date
                         };
 
-                        match roll_chance(50) {
+                        match roll_chance(50, rng) {
                             true => commands.spawn((new_born, CitizenOf { colony }, Youngling, Male)),
                             false => commands.spawn((new_born, CitizenOf { colony }, Youngling, Female { children_had: 0 } )),
                         };
@@ -71,12 +74,14 @@ pub fn citizen_births(
 pub fn init_miscarriage(
     mut commands: Commands,
     mut event_reader: EventReader<MonthChanged>,
-    mut pregnant_women: Query<(Entity, &Citizen), With<Pregnancy>>,
+    mut pregnant_women: Query<(Entity, &Citizen, &CitizenOf), With<Pregnancy>>,
     game_date: Res<GameDate>,
+    mut sim_rng: ResMut<SimRng>,
 ) {
     for _ in event_reader.read() {
-        for (entity, w_citizen) in &mut pregnant_women {
-            if miscarriage_chance(game_date.date.years_since(w_citizen.birthday).unwrap() as u8) {
+        for (entity, w_citizen, citizen_of) in &mut pregnant_women {
+            let age = game_date.date.years_since(w_citizen.birthday).unwrap() as u8;
+            if miscarriage_chance(age, sim_rng.colony(citizen_of.colony)) {
                 commands.get_entity(entity).map(|mut e| {
                     e.remove::<Pregnancy>();
                     e.try_insert(Employable);
@@ -86,15 +91,15 @@ pub fn init_miscarriage(
     }
 }
 
-pub fn miscarriage_chance(age: u8) -> bool {
+pub fn miscarriage_chance(age: u8, rng: &mut impl Rng) -> bool {
     match age {
-        18..=19 => roll_chance(17),
-        20..=24 => roll_chance(11),
-        25..=29 => roll_chance(10),
-        30..=34 => roll_chance(11),
-        35..=39 => roll_chance(17),
-        40..=44 => roll_chance(33),
-        45.. => roll_chance(57),
+        18..=19 => roll_chance(17, rng),
+        20..=24 => roll_chance(11, rng),
+        25..=29 => roll_chance(10, rng),
+        30..=34 => roll_chance(11, rng),
+        35..=39 => roll_chance(17, rng),
+        40..=44 => roll_chance(33, rng),
+        45.. => roll_chance(57, rng),
         _ => false,
     }
 }
@@ -103,12 +108,14 @@ pub fn init_ovulation(
     mut commands: Commands,
     mut event_reader: EventReader<MonthChanged>,
     game_date: Res<GameDate>,
-    women: Query<(Entity, &mut Citizen), (With<Female>, Without<Pregnancy>, Without<Ovulation>)>,
+    women: Query<(Entity, &CitizenOf), (With<Female>, Without<Pregnancy>, Without<Ovulation>)>,
+    mut sim_rng: ResMut<SimRng>,
 ) {
     for _ in event_reader.read() {
-        for (entity, _) in &women {
+        for (entity, citizen_of) in &women {
+            let offset = sim_rng.colony(citizen_of.colony).gen_range(5..=20);
             let ovulation_start_date =
-                game_date.date + chrono::Duration::days(thread_rng().gen_range(5..=20) as i64);
+                game_date.date + chrono::Duration::days(offset as i64);
 
             commands.get_entity(entity).map(|mut e| {
                 e.try_insert(Ovulation {
@@ -123,12 +130,13 @@ pub fn end_ovulation(
     mut commands: Commands,
     mut event_reader: EventReader<DateChanged>,
     game_date: Res<GameDate>,
-    women: Query<(Entity, &Citizen, &Ovulation)>,
+    women: Query<(Entity, &CitizenOf, &Ovulation)>,
+    mut sim_rng: ResMut<SimRng>,
 ) {
     for _ in event_reader.read() {
-        for (entity, _, ovulation) in &women {
+        for (entity, citizen_of, ovulation) in &women {
             if ovulation.ovulation_start_date
-                + chrono::Duration::days(thread_rng().gen_range(5..=6))
+                + chrono::Duration::days(sim_rng.colony(citizen_of.colony).gen_range(5..=6))
                 == game_date.date
             {
                 commands.get_entity(entity).map(|mut e| {
@@ -144,7 +152,7 @@ pub fn init_pregnancies(
     game_date: Res<GameDate>,
     mut event_reader: EventReader<DateChanged>,
     mut citizens: Query<
-        (Entity, &mut Citizen),
+        (Entity, &mut Citizen, &CitizenOf),
         (
             With<Ovulation>,
             With<Female>,
@@ -152,12 +160,17 @@ pub fn init_pregnancies(
             Without<Pregnancy>,
         ),
     >,
+    colonies: Query<&WorldConfig>,
+    mut sim_rng: ResMut<SimRng>,
 ) {
     for _ in event_reader.read() {
-        for (w_entity, w_citizen) in &mut citizens {
-            if pregnancy_desire() {
-                if pregnancy_chance(game_date.date.years_since(w_citizen.birthday).unwrap() as u8) {
-                    let pregnancy_term = thread_rng().gen_range(270..=280);
+        for (w_entity, w_citizen, citizen_of) in &mut citizens {
+            let rng = sim_rng.colony(citizen_of.colony);
+            if pregnancy_desire(rng) {
+                if pregnancy_chance(game_date.date.years_since(w_citizen.birthday).unwrap() as u8, rng) {
+                    let (shortest, longest) = colonies.get(citizen_of.colony).unwrap()
+                        .population().pregnancy_term();
+                    let pregnancy_term = rng.gen_range(shortest..=longest);
                     commands.get_entity(w_entity).map(|mut e| {
                         e.try_insert(Pregnancy {
                             baby_due_date: game_date
@@ -172,28 +185,28 @@ pub fn init_pregnancies(
     }
 }
 
-pub fn pregnancy_chance(age: u8) -> bool {
+pub fn pregnancy_chance(age: u8, rng: &mut impl Rng) -> bool {
     let age_f32 = age as f32;
     let pregnancy_chance = -0.0005893368566 * age_f32.powf(4.0)
         + 0.0730945581099 * age_f32.powf(3.0)
         - 3.3813849411076 * age_f32.powf(2.0)
         + 66.904528373158 * age_f32
         - 390.6749280259455;
-    roll_chance(pregnancy_chance as u8)
+    roll_chance(pregnancy_chance as u8, rng)
 }
 
-pub fn pregnancy_desire() -> bool {
-    let economy: f32 = thread_rng().gen_range(0.0..=1.0);
-    let urbanization: f32 = thread_rng().gen_range(0.0..=1.0);
-    let demand: f32 = thread_rng().gen_range(0.0..=1.0);
-    let survivability: f32 = thread_rng().gen_range(0.0..=1.0);
+pub fn pregnancy_desire(rng: &mut impl Rng) -> bool {
+    let economy: f32 = rng.gen_range(0.0..=1.0);
+    let urbanization: f32 = rng.gen_range(0.0..=1.0);
+    let demand: f32 = rng.gen_range(0.0..=1.0);
+    let survivability: f32 = rng.gen_range(0.0..=1.0);
 
     let mut preg_chance =
         2.1 * urbanization * (economy / economy) * demand * (1.0 - urbanization) * survivability;
 
     preg_chance = preg_chance * 100.0;
 
-    roll_chance(preg_chance as u8)
+    roll_chance(preg_chance as u8, rng)
 }
 
 
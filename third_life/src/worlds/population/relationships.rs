@@ -1,8 +1,8 @@
 use std::{f32::consts, iter::zip};
 use crate::{
     common::utils::roll_chance,
-    time::{DateChanged, GameDate, MonthChanged},
-    SimulationState, worlds::WorldColony,
+    time::{DailyTick, DateChanged, GameDate, MonthChanged},
+    worlds::{config::WorldConfig, SimulationSet, WorldColony},
 };
 use bevy::{prelude::*, transform::commands};
 use bevy_egui::{egui::{Window, ahash::{HashMap, HashMapExt}}, EguiContexts};
@@ -11,7 +11,7 @@ use rand::{thread_rng, Rng, rngs::ThreadRng};
 use rand_distr::{num_traits::{Float, real::Real}, Distribution, SkewNormal};
 use rnglib::{Language, RNG};
 
-use super::{components::*, events::CitizenDied};
+use super::{components::*, events::CitizenDied, dying::infant_mortality};
 
 pub struct RelationshipsPlugin;
 
@@ -19,11 +19,11 @@ impl Plugin for RelationshipsPlugin {
     fn build(&self, app: &mut App) {
         app
             .add_systems(
-                Update,
+                DailyTick,
                 (
+                    create_widowed,
                     init_couples,
-                    create_widowed
-                ).run_if(in_state(SimulationState::Running))
+                ).chain().after(infant_mortality).in_set(SimulationSet::Lifecycle)
             );
     }
 }
@@ -32,7 +32,7 @@ pub fn init_couples(
     mut commands: Commands,
     mut event_reader: EventReader<DateChanged>,
     game_date: Res<GameDate>,
-    colonies: Query<Entity, With<WorldColony>>,
+    colonies: Query<(Entity, &WorldConfig), With<WorldColony>>,
     men: Query<(Entity, &Citizen, &CitizenOf), (With<Male>, Without<Spouse>)>,
     women: Query<
         (Entity, &Citizen, &CitizenOf),
@@ -40,7 +40,8 @@ pub fn init_couples(
     >,
 ) {
     for _ in event_reader.read() {
-        for colony in &colonies {
+        for (colony, world) in &colonies {
+            let marriage_age = world.population().marriage_age();
             let mut colony_available_men: Vec<Entity> = men
                 .iter()
                 .filter_map(|(entity, _, m_citizen_of)| {
@@ -63,7 +64,7 @@ pub fn init_couples(
                 .collect::<Vec<_>>();
 
             for (woman_entity, w_birthday) in colony_available_women {
-                if game_date.date.years_since(w_birthday).unwrap() > 18 {
+                if game_date.date.years_since(w_birthday).unwrap() > marriage_age {
                     if let Some(man_entity) = colony_available_men.pop() {
                         commands.get_entity(woman_entity).map(|mut e| {
                             e.try_insert(Spouse { spouse: man_entity });
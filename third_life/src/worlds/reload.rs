@@ -0,0 +1,89 @@
+//! Takes over changes to `worlds.json` while the simulation runs.
+//!
+//! The government, the environment and the seasons of a world are applied
+//! to its colony right away, so new spending policies and environment
+//! factors take effect on the next day. Everything else, like the starting
+//! population or the farms, was only used to set the colony up. Changes to
+//! those, and worlds that were added or removed, are logged and collected in
+//! [`UnappliedConfigChanges`] instead.
+//!
+//! A run continued from a snapshot is treated the same way. Its colonies are
+//! restored with the [`WorldConfig`] they had when the snapshot was taken,
+//! so changes to the files and [`crate::config::ConfigOverrides`] made since
+//! then are taken over once they were restored.
+
+use bevy::prelude::*;
+
+use crate::{
+    config::{ConfigChanged, UnappliedConfigChanges},
+    SimulationState,
+};
+
+use super::{
+    config::{PolicySwitch, WorldConfig, WorldsConfig},
+    snapshot::{restore_snapshot, LoadedSnapshot},
+    wealth::components::WealthAndSpending,
+};
+
+pub struct ReloadPlugin;
+
+impl Plugin for ReloadPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(
+                OnEnter(SimulationState::Running),
+                apply_to_restored_colonies
+                    .after(restore_snapshot)
+                    .run_if(resource_exists::<LoadedSnapshot>)
+            )
+            .add_systems(Update, apply_worlds_config.run_if(on_event::<ConfigChanged<WorldsConfig>>()));
+    }
+}
+
+fn apply_worlds_config(
+    mut changed: EventReader<ConfigChanged<WorldsConfig>>,
+    worlds_config: Res<WorldsConfig>,
+    unapplied: ResMut<UnappliedConfigChanges>,
+    colonies: Query<(&mut WorldConfig, &mut WealthAndSpending)>,
+) {
+    changed.clear();
+    take_over(&worlds_config, unapplied, colonies);
+}
+
+fn apply_to_restored_colonies(
+    worlds_config: Res<WorldsConfig>,
+    unapplied: ResMut<UnappliedConfigChanges>,
+    colonies: Query<(&mut WorldConfig, &mut WealthAndSpending)>,
+) {
+    take_over(&worlds_config, unapplied, colonies);
+}
+
+fn take_over(
+    worlds_config: &WorldsConfig,
+    mut unapplied: ResMut<UnappliedConfigChanges>,
+    mut colonies: Query<(&mut WorldConfig, &mut WealthAndSpending)>,
+) {
+    let mut flag = |change: String| {
+        warn!("{change}");
+        unapplied.0.push(change);
+    };
+
+    for (mut config, mut wealth) in &mut colonies {
+        let Some(new) = worlds_config.worlds().into_iter().find(|w| w.name() == config.name()) else {
+            flag(format!("`{}` was removed, it stays until the next run", config.name()));
+            continue;
+        };
+        if config.government() != new.government() {
+            wealth.switch_policy(&PolicySwitch::from(new.government()));
+        }
+        for field in config.apply_live(&new) {
+            flag(format!("`{field}` of `{}` changed, it only takes effect on the next run", new.name()));
+        }
+    }
+
+    for world in worlds_config.worlds() {
+        if !colonies.iter().any(|(config, _)| config.name() == world.name()) {
+            flag(format!("`{}` was added, it only takes part in the next run", world.name()));
+        }
+    }
+}
@@ -0,0 +1,159 @@
+//! Scripted shocks on a timeline, loaded from the `scenario.json` of the
+//! config folder.
+//!
+//! Every action has a date and optionally the world it targets. Once the
+//! [`GameDate`] reaches that date the action is applied at the start of the
+//! day and a [`ScenarioActionApplied`] event is sent for every world it was
+//! applied to.
+//!
+//! ```json
+//! {
+//!     "actions": [
+//!         { "date": "2160-01-01", "world": "Earth", "action": "famine", "resource": "carb", "share": 0.5 },
+//!         { "date": "2162-06-01", "action": "plague", "daily_death_chance": 0.001, "days": 90 },
+//!         { "date": "2165-01-01", "world": "Mars", "action": "policy_switch", "sanitation_spending": 50 }
+//!     ]
+//! }
+//! ```
+//!
+//! Only actions dated after the day the run starts on are applied, so a run
+//! continued from a snapshot does not repeat the actions it already went
+//! through. A plague that is still going on when a snapshot is taken is part
+//! of the snapshot and goes on after loading it.
+
+use bevy::prelude::*;
+use chrono::{Days, NaiveDate};
+
+use crate::{
+    time::{init_start_date, DailyTick, GameDate},
+    SimulationState,
+};
+
+use super::{
+    config::{
+        FamineResource, ScenarioAction, ScenarioActionKind, ScenarioConfig,
+        ScenarioConfigPlugin,
+    },
+    food::components::{CarbResource, FoodResource, MeatResource, ResourceOf},
+    population::components::Plague,
+    snapshot::restore_snapshot,
+    wealth::components::WealthAndSpending,
+    init_colonies, SimulationSet, WorldEntity,
+};
+
+pub struct ScenarioPlugin;
+
+impl Plugin for ScenarioPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_plugins(ScenarioConfigPlugin)
+            .add_event::<ScenarioActionApplied>()
+            .add_systems(
+                OnEnter(SimulationState::Running),
+                init_timeline
+                    .after(init_start_date)
+                    .after(init_colonies)
+                    .after(restore_snapshot)
+            )
+            .add_systems(
+                DailyTick,
+                apply_actions
+                    .before(SimulationSet::Production)
+                    .run_if(resource_exists::<ScenarioTimeline>)
+            );
+    }
+}
+
+/// Sent for every world an action of the scenario was applied to.
+#[derive(Event, Debug, Clone)]
+pub struct ScenarioActionApplied {
+    pub colony: Entity,
+    pub date: NaiveDate,
+    pub action: ScenarioActionKind,
+}
+
+/// Actions of the scenario that still have to be applied, ordered by date.
+#[derive(Resource, Debug)]
+pub struct ScenarioTimeline {
+    actions: Vec<ScenarioAction>,
+    next: usize,
+}
+
+fn init_timeline(
+    mut commands: Commands,
+    scenario: Res<ScenarioConfig>,
+    game_date: Res<GameDate>,
+    colonies: Query<&WorldEntity>,
+) {
+    let mut actions = scenario.actions();
+    for world in actions.iter().filter_map(|a| a.world.as_ref()) {
+        if !colonies.iter().any(|c| c.name() == world) {
+            panic!(r#"
+                The scenario contains an action for the world {world} which
+                does not exist. Check the names in `worlds.json`.
+            "#);
+        }
+    }
+    actions.sort_by_key(|a| a.date);
+    let next = actions.partition_point(|a| a.date <= game_date.date);
+    if actions.len() > next {
+        info!("Scenario with {} upcoming actions", actions.len() - next);
+    }
+    commands.insert_resource(ScenarioTimeline { actions, next });
+}
+
+type FoodStocks<'a> = (
+    &'a ResourceOf,
+    Option<&'a mut CarbResource>,
+    Option<&'a mut MeatResource>,
+    Option<&'a mut FoodResource>,
+);
+
+fn apply_actions(
+    mut commands: Commands,
+    mut timeline: ResMut<ScenarioTimeline>,
+    mut applied: EventWriter<ScenarioActionApplied>,
+    game_date: Res<GameDate>,
+    mut colonies: Query<(Entity, &WorldEntity, &mut WealthAndSpending)>,
+    mut resources: Query<FoodStocks>,
+) {
+    let date = game_date.date;
+    while let Some(action) = timeline.actions.get(timeline.next) {
+        if action.date > date {
+            break;
+        }
+        for (colony, world, mut wealth) in &mut colonies {
+            if action.world.as_ref().is_some_and(|w| w != world.name()) {
+                continue;
+            }
+            match &action.kind {
+                ScenarioActionKind::Famine { resource, share } => {
+                    let remaining = 1. - share.clamp(0., 1.);
+                    for (of, carb, meat, food) in &mut resources {
+                        if of.colony != colony {
+                            continue;
+                        }
+                        let amount = match resource {
+                            FamineResource::Carb => carb.map(|r| &mut r.into_inner().amount),
+                            FamineResource::Meat => meat.map(|r| &mut r.into_inner().amount),
+                            FamineResource::Food => food.map(|r| &mut r.into_inner().amount),
+                        };
+                        if let Some(amount) = amount {
+                            *amount *= remaining;
+                        }
+                    }
+                }
+                ScenarioActionKind::Plague { daily_death_chance, days } => {
+                    commands.entity(colony).insert(Plague {
+                        daily_death_chance: *daily_death_chance,
+                        until: date + Days::new(days.saturating_sub(1)),
+                    });
+                }
+                ScenarioActionKind::PolicySwitch(switch) => wealth.switch_policy(switch),
+            }
+            info!("Scenario action on {date} in {}: {:?}", world.name(), action.kind);
+            applied.send(ScenarioActionApplied { colony, date, action: action.kind.clone() });
+        }
+        timeline.next += 1;
+    }
+}
@@ -0,0 +1,92 @@
+//! Seasons of the individual worlds.
+//!
+//! The [`Season`] resource follows the calendar on the northern hemisphere.
+//! Every world has its own [`WorldSeason`] instead, shifted by the
+//! `seasons` of its [`WorldConfig`], and a [`WorldSeasonChanged`] event is
+//! sent on the first day of each of them. Systems that depend on the time of
+//! the year, like the harvest and the breeding of cows, should use these so
+//! worlds on the southern hemisphere get their summer in December.
+
+use bevy::prelude::*;
+use chrono::{Days, Months, NaiveDate};
+
+use crate::{
+    time::{init_start_date, DailyTick, GameDate, Season},
+    SimulationState,
+};
+
+use super::{
+    config::{Hemisphere, SeasonsConfig, WorldConfig},
+    init_colonies,
+    snapshot::restore_snapshot,
+    SimulationSet,
+};
+
+pub struct SeasonsPlugin;
+
+impl Plugin for SeasonsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<WorldSeasonChanged>()
+            .add_systems(
+                OnEnter(SimulationState::Running),
+                init_world_seasons
+                    .after(init_start_date)
+                    .after(init_colonies)
+                    .after(restore_snapshot)
+            )
+            .add_systems(DailyTick, update_world_seasons.before(SimulationSet::Production));
+    }
+}
+
+/// Current season of a world.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WorldSeason(pub Season);
+
+/// Sent on the first day of a season of a world.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct WorldSeasonChanged {
+    pub colony: Entity,
+    pub season: Season,
+}
+
+impl SeasonsConfig {
+    /// Season of the world with this config on the given date.
+    pub fn season_on(&self, date: NaiveDate) -> Season {
+        let date = match self.hemisphere() {
+            Hemisphere::Northern => date,
+            Hemisphere::Southern => date + Months::new(6),
+        };
+        let offset = Days::new(self.offset_days().unsigned_abs());
+        let date = match self.offset_days() {
+            days if days < 0 => date - offset,
+            _ => date + offset,
+        };
+        Season::of(date)
+    }
+}
+
+fn init_world_seasons(
+    mut commands: Commands,
+    game_date: Res<GameDate>,
+    colonies: Query<(Entity, &WorldConfig)>,
+) {
+    for (colony, config) in &colonies {
+        let season = config.seasons().season_on(game_date.date);
+        commands.entity(colony).insert(WorldSeason(season));
+    }
+}
+
+fn update_world_seasons(
+    game_date: Res<GameDate>,
+    mut changed: EventWriter<WorldSeasonChanged>,
+    mut colonies: Query<(Entity, &WorldConfig, &mut WorldSeason)>,
+) {
+    for (colony, config, mut world_season) in &mut colonies {
+        let season = config.seasons().season_on(game_date.date);
+        if world_season.0 != season {
+            world_season.0 = season;
+            changed.send(WorldSeasonChanged { colony, season });
+        }
+    }
+}
@@ -0,0 +1,485 @@
+//! Saving the whole state of a run to a file and starting a new run from it.
+//!
+//! A [`Snapshot`] is a plain json file. Components that point to other
+//! entities, like [`Spouse`] or [`CowOf`], can't be stored as they are since
+//! an [`Entity`] is only valid inside of the app that created it. Instead
+//! every colony, citizen and farm is given its position in the snapshot and
+//! references are stored as that index. When loading the entities are spawned
+//! first and the references are mapped back to the new entities afterwards.
+//!
+//! Loading works by inserting a [`LoadedSnapshot`] before the app is started,
+//! see [`crate::cli::SimulationArgs`]. The config folder stored in the
+//! snapshot is selected automatically, and once the configs are loaded the
+//! snapshot replaces the usual initialization of colonies, citizens and farms.
+
+use std::{collections::HashMap, fmt, fs, io, path::{Path, PathBuf}};
+
+use bevy::prelude::*;
+use chrono::NaiveDate;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::{ConfigOverride, ConfigOverrides, SelectedConfigPath},
+    rng::SimRng,
+    time::{init_start_date, GameDate, Season},
+    SimulationState,
+};
+
+use super::{
+    calendar::LocalCalendar,
+    config::WorldConfig,
+    env_and_infra::components::SanitationInfrastructure,
+    food::components::*,
+    init_colonies,
+    population::components::*,
+    stability::components::StabilityWindow,
+    wealth::components::WealthAndSpending,
+    WorldColony, WorldColonyBundle,
+};
+
+pub struct SnapshotPlugin;
+
+impl Plugin for SnapshotPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<SaveSnapshot>()
+            .add_systems(
+                OnEnter(SimulationState::Running),
+                restore_snapshot
+                    .after(init_start_date)
+                    .before(init_colonies)
+                    .run_if(resource_exists::<LoadedSnapshot>)
+            )
+            .add_systems(
+                Update,
+                save_snapshot.run_if(
+                    in_state(SimulationState::Running).and_then(on_event::<SaveSnapshot>())
+                )
+            );
+    }
+}
+
+/// Asks for the current state to be written to the given file.
+#[derive(Event, Debug, Clone)]
+pub struct SaveSnapshot {
+    pub path: PathBuf,
+}
+
+/// Snapshot the run is started from. While it exists the usual
+/// initialization systems are skipped.
+#[derive(Resource)]
+pub struct LoadedSnapshot(pub Snapshot);
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    Io(io::Error),
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::Io(err) => write!(f, "could not access the snapshot file: {err}"),
+            SnapshotError::Json(err) => write!(f, "the snapshot file is not valid: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+impl From<io::Error> for SnapshotError {
+    fn from(err: io::Error) -> Self {
+        SnapshotError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for SnapshotError {
+    fn from(err: serde_json::Error) -> Self {
+        SnapshotError::Json(err)
+    }
+}
+
+/// Everything needed to continue a run.
+#[derive(Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Path of the config folder inside of `assets`, same as the
+    /// [`SelectedConfigPath`] of the run.
+    pub config_path: String,
+    /// The [`ConfigOverrides`] the run was started with.
+    #[serde(default)]
+    pub overrides: Vec<ConfigOverride>,
+    pub date: NaiveDate,
+    pub seed: u64,
+    pub colonies: Vec<ColonySnapshot>,
+    pub citizens: Vec<CitizenSnapshot>,
+    pub wheat_farms: Vec<FarmSnapshot<WheatFarm>>,
+    pub cow_farms: Vec<FarmSnapshot<CowFarm>>,
+    pub cows: Vec<CowSnapshot>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ColonySnapshot {
+    pub config: WorldConfig,
+    pub population: Population,
+    /// The [`SanitationInfrastructure`] is not stored since it only depends
+    /// on the wealth and is calculated again when loading.
+    pub wealth: WealthAndSpending,
+    pub food: FoodResource,
+    pub carb: CarbResource,
+    pub meat: MeatResource,
+    pub rng: ChaCha8Rng,
+    /// Missing in snapshots taken before worlds had their own calendars.
+    #[serde(default)]
+    pub calendar: Option<LocalCalendar>,
+    /// The plague of a scenario that is still going on.
+    #[serde(default)]
+    pub plague: Option<Plague>,
+    /// Missing in snapshots taken before worlds were labeled by their
+    /// stability, the window starts empty then.
+    #[serde(default)]
+    pub stability: Option<StabilityWindow>,
+}
+
+/// A citizen, `colony` and `spouse` are indices into [`Snapshot::colonies`]
+/// and [`Snapshot::citizens`].
+#[derive(Serialize, Deserialize)]
+pub struct CitizenSnapshot {
+    pub citizen: Citizen,
+    pub colony: usize,
+    /// `None` for men.
+    pub female: Option<Female>,
+    pub spouse: Option<usize>,
+    pub widowed: bool,
+    pub ovulation: Option<Ovulation>,
+    pub pregnancy: Option<Pregnancy>,
+    pub starving: Option<Starving>,
+    pub youngling: bool,
+    pub retiree: bool,
+    pub employed: bool,
+    pub employable: bool,
+    pub job: Option<Job>,
+}
+
+/// Farm a citizen works at, as index into [`Snapshot::wheat_farms`] or
+/// [`Snapshot::cow_farms`].
+#[derive(Serialize, Deserialize)]
+pub enum Job {
+    WheatFarmer(usize),
+    CowFarmer(usize),
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct FarmSnapshot<T> {
+    pub farm: T,
+    pub colony: usize,
+}
+
+/// A cow, `farm` is an index into [`Snapshot::cow_farms`].
+#[derive(Serialize, Deserialize)]
+pub struct CowSnapshot {
+    pub cow: Cow,
+    pub farm: usize,
+    pub bull: bool,
+    pub breeder: bool,
+}
+
+impl Snapshot {
+    pub fn read(path: &Path) -> Result<Self, SnapshotError> {
+        let file = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&file)?)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), SnapshotError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Collects the current state of the world. Panics if called before the
+    /// simulation is running.
+    pub fn take(world: &mut World) -> Self {
+        let colony_entities = world
+            .query_filtered::<Entity, With<WorldColony>>()
+            .iter(world)
+            .collect::<Vec<_>>();
+        let colony_ids = index_map(&colony_entities);
+
+        let wheat_farm_entities = world
+            .query_filtered::<Entity, With<WheatFarm>>()
+            .iter(world)
+            .collect::<Vec<_>>();
+        let wheat_farm_ids = index_map(&wheat_farm_entities);
+
+        let cow_farm_entities = world
+            .query_filtered::<Entity, With<CowFarm>>()
+            .iter(world)
+            .collect::<Vec<_>>();
+        let cow_farm_ids = index_map(&cow_farm_entities);
+
+        let citizen_entities = world
+            .query_filtered::<Entity, With<Citizen>>()
+            .iter(world)
+            .collect::<Vec<_>>();
+        let citizen_ids = index_map(&citizen_entities);
+
+        let mut food = HashMap::new();
+        for (resource, of) in world.query::<(&FoodResource, &ResourceOf)>().iter(world) {
+            food.insert(of.colony, resource.amount);
+        }
+        let mut carb = HashMap::new();
+        for (resource, of) in world.query::<(&CarbResource, &ResourceOf)>().iter(world) {
+            carb.insert(of.colony, resource.amount);
+        }
+        let mut meat = HashMap::new();
+        for (resource, of) in world.query::<(&MeatResource, &ResourceOf)>().iter(world) {
+            meat.insert(of.colony, resource.amount);
+        }
+
+        let mut sim_rng = world.resource_mut::<SimRng>();
+        let seed = sim_rng.seed();
+        let rngs = colony_entities.iter()
+            .map(|colony| sim_rng.colony(*colony).clone())
+            .collect::<Vec<_>>();
+
+        let colonies = colony_entities.iter().zip(rngs)
+            .map(|(colony, rng)| {
+                let e = world.entity(*colony);
+                let population = e.get::<Population>().unwrap();
+                let wealth = e.get::<WealthAndSpending>().unwrap();
+                ColonySnapshot {
+                    config: e.get::<WorldConfig>().unwrap().clone(),
+                    population: Population { ..*population },
+                    wealth: WealthAndSpending {
+                        policy: wealth.policy.clone(),
+                        ..*wealth
+                    },
+                    food: FoodResource { amount: food[colony] },
+                    carb: CarbResource { amount: carb[colony] },
+                    meat: MeatResource { amount: meat[colony] },
+                    rng,
+                    calendar: e.get::<LocalCalendar>().cloned(),
+                    plague: e.get::<Plague>().map(|p| Plague { ..*p }),
+                    stability: e.get::<StabilityWindow>().cloned(),
+                }
+            })
+            .collect();
+
+        let citizens = citizen_entities.iter()
+            .map(|citizen| {
+                let e = world.entity(*citizen);
+                let job = e.get::<WheatFarmer>()
+                    .and_then(|f| wheat_farm_ids.get(&f.farm))
+                    .map(|i| Job::WheatFarmer(*i))
+                    .or_else(|| e.get::<CowFarmer>()
+                        .and_then(|f| cow_farm_ids.get(&f.farm))
+                        .map(|i| Job::CowFarmer(*i))
+                    );
+                CitizenSnapshot {
+                    citizen: e.get::<Citizen>().unwrap().clone(),
+                    colony: colony_ids[&e.get::<CitizenOf>().unwrap().colony],
+                    female: e.get::<Female>().map(|f| Female { children_had: f.children_had }),
+                    // INFO: The spouse might have died this very day and not
+                    // be removed yet, in which case the citizen is single.
+                    spouse: e.get::<Spouse>().and_then(|s| citizen_ids.get(&s.spouse)).copied(),
+                    widowed: e.contains::<Widowed>(),
+                    ovulation: e.get::<Ovulation>().map(|o| Ovulation { ..*o }),
+                    pregnancy: e.get::<Pregnancy>().map(|p| Pregnancy { ..*p }),
+                    starving: e.get::<Starving>().map(|s| Starving { ..*s }),
+                    youngling: e.contains::<Youngling>(),
+                    retiree: e.contains::<Retiree>(),
+                    employed: e.contains::<Employed>(),
+                    employable: e.contains::<Employable>(),
+                    job,
+                }
+            })
+            .collect();
+
+        let wheat_farms = wheat_farm_entities.iter()
+            .map(|farm| {
+                let e = world.entity(*farm);
+                FarmSnapshot {
+                    farm: WheatFarm { ..*e.get::<WheatFarm>().unwrap() },
+                    colony: colony_ids[&e.get::<WheatFarmOf>().unwrap().colony],
+                }
+            })
+            .collect();
+
+        let cow_farms = cow_farm_entities.iter()
+            .map(|farm| {
+                let e = world.entity(*farm);
+                FarmSnapshot {
+                    farm: CowFarm { ..*e.get::<CowFarm>().unwrap() },
+                    colony: colony_ids[&e.get::<CowFarmOf>().unwrap().colony],
+                }
+            })
+            .collect();
+
+        let cows = world
+            .query::<(&Cow, &CowOf, Has<IsBull>, Has<IsBreeder>)>()
+            .iter(world)
+            .map(|(cow, cow_of, bull, breeder)| CowSnapshot {
+                cow: Cow { ..*cow },
+                farm: cow_farm_ids[&cow_of.cow_farm],
+                bull,
+                breeder,
+            })
+            .collect();
+
+        Snapshot {
+            config_path: world.resource::<SelectedConfigPath>().0.clone(),
+            overrides: world.resource::<ConfigOverrides>().0.clone(),
+            date: world.resource::<GameDate>().date,
+            seed,
+            colonies,
+            citizens,
+            wheat_farms,
+            cow_farms,
+            cows,
+        }
+    }
+
+    /// Spawns everything in the snapshot and sets the date and the random
+    /// number generator to where the snapshot was taken.
+    pub fn restore(&self, world: &mut World) {
+        let mut sim_rng = SimRng::new(self.seed);
+        let colonies = self.colonies.iter()
+            .map(|colony| {
+                let mut sanitation = SanitationInfrastructure::default();
+                sanitation.update(colony.wealth.total_sanitation_spending());
+                let entity = world.spawn(WorldColonyBundle::new(colony.config.clone()))
+                    .insert((
+                        Population { ..colony.population },
+                        WealthAndSpending {
+                            policy: colony.wealth.policy.clone(),
+                            ..colony.wealth
+                        },
+                        sanitation,
+                    ))
+                    .id();
+                if let Some(calendar) = &colony.calendar {
+                    world.entity_mut(entity).insert(calendar.clone());
+                }
+                if let Some(plague) = &colony.plague {
+                    world.entity_mut(entity).insert(Plague { ..*plague });
+                }
+                if let Some(window) = &colony.stability {
+                    world.entity_mut(entity).insert(window.clone());
+                }
+                world.spawn((FoodResource { ..colony.food }, ResourceOf { colony: entity }));
+                world.spawn((CarbResource { ..colony.carb }, ResourceOf { colony: entity }));
+                world.spawn((MeatResource { ..colony.meat }, ResourceOf { colony: entity }));
+                sim_rng.insert_colony(entity, colony.rng.clone());
+                entity
+            })
+            .collect::<Vec<_>>();
+        world.insert_resource(sim_rng);
+
+        let wheat_farms = self.wheat_farms.iter()
+            .map(|f| world.spawn((
+                WheatFarm { ..f.farm },
+                WheatFarmOf { colony: colonies[f.colony] },
+            )).id())
+            .collect::<Vec<_>>();
+
+        let cow_farms = self.cow_farms.iter()
+            .map(|f| world.spawn((
+                CowFarm { ..f.farm },
+                CowFarmOf { colony: colonies[f.colony] },
+            )).id())
+            .collect::<Vec<_>>();
+
+        for cow in &self.cows {
+            let mut e = world.spawn((
+                Cow { ..cow.cow },
+                CowOf { cow_farm: cow_farms[cow.farm] },
+            ));
+            if cow.bull {
+                e.insert(IsBull);
+            }
+            if cow.breeder {
+                e.insert(IsBreeder);
+            }
+        }
+
+        let citizens = self.citizens.iter()
+            .map(|c| world.spawn((
+                c.citizen.clone(),
+                CitizenOf { colony: colonies[c.colony] },
+            )).id())
+            .collect::<Vec<_>>();
+
+        for (c, entity) in self.citizens.iter().zip(&citizens) {
+            let mut e = world.entity_mut(*entity);
+            match &c.female {
+                Some(female) => e.insert(Female { ..*female }),
+                None => e.insert(Male),
+            };
+            if let Some(spouse) = c.spouse {
+                e.insert(Spouse { spouse: citizens[spouse] });
+            }
+            if c.widowed {
+                e.insert(Widowed);
+            }
+            if let Some(ovulation) = &c.ovulation {
+                e.insert(Ovulation { ..*ovulation });
+            }
+            if let Some(pregnancy) = &c.pregnancy {
+                e.insert(Pregnancy { ..*pregnancy });
+            }
+            if let Some(starving) = &c.starving {
+                e.insert(Starving { ..*starving });
+            }
+            if c.youngling {
+                e.insert(Youngling);
+            }
+            if c.retiree {
+                e.insert(Retiree);
+            }
+            if c.employed {
+                e.insert(Employed);
+            }
+            if c.employable {
+                e.insert(Employable);
+            }
+            match c.job {
+                Some(Job::WheatFarmer(farm)) => { e.insert(WheatFarmer { farm: wheat_farms[farm] }); },
+                Some(Job::CowFarmer(farm)) => { e.insert(CowFarmer { farm: cow_farms[farm] }); },
+                None => (),
+            }
+        }
+
+        world.resource_mut::<GameDate>().date = self.date;
+        world.insert_resource(Season::of(self.date));
+    }
+}
+
+fn index_map(entities: &[Entity]) -> HashMap<Entity, usize> {
+    entities.iter().enumerate().map(|(i, e)| (*e, i)).collect()
+}
+
+pub(crate) fn restore_snapshot(world: &mut World) {
+    let LoadedSnapshot(snapshot) = world.remove_resource::<LoadedSnapshot>().unwrap();
+    snapshot.restore(world);
+    info!(
+        "Restored snapshot from {} with {} citizens",
+        snapshot.date, snapshot.citizens.len()
+    );
+    world.insert_resource(LoadedSnapshot(snapshot));
+}
+
+pub(crate) fn save_snapshot(world: &mut World) {
+    let paths = world.resource_mut::<Events<SaveSnapshot>>()
+        .drain()
+        .map(|e| e.path)
+        .collect::<Vec<_>>();
+    let snapshot = Snapshot::take(world);
+    for path in paths {
+        match snapshot.write(&path) {
+            Ok(()) => info!("Saved snapshot to {}", path.display()),
+            Err(err) => error!("Saving the snapshot to {} failed: {err}", path.display()),
+        }
+    }
+}
@@ -0,0 +1,198 @@
+//! Labels every world as growing, stable, oscillating, declining or
+//! collapsed, based on rolling windows of its population, food stocks and
+//! deaths.
+//!
+//! Once per game day a [`StabilitySample`] is added to the
+//! [`StabilityWindow`] of every colony, dropping the ones older than the
+//! `window_days` of the [`StabilityConfig`]. A straight line is fitted
+//! through the population of the window:
+//!
+//! - a world with less citizens than `collapse_population` has collapsed,
+//! - a world whose trend loses more than `stable_growth` per year is
+//!   declining, one that gains more is growing,
+//! - a world that swings around its trend by more than
+//!   `oscillation_amplitude`, crossing it at least twice in both directions,
+//!   is oscillating,
+//! - every other world is stable.
+//!
+//! The trends of the population and the food stocks also estimate how long
+//! it takes until the world collapses and until it runs out of food.
+//!
+//! The population is counted from the citizens of the colony, since the
+//! `count` of [`super::population::components::Population`] is not lowered
+//! when citizens die.
+
+pub mod components;
+
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+
+use crate::{
+    config::{StabilityConfig, ThirdLifeConfig},
+    time::DailyTick,
+    SimulationState,
+};
+
+use self::components::{Stability, StabilityLabel, StabilitySample, StabilityWindow};
+
+use super::{
+    food::components::{FoodResource, ResourceOf},
+    init_colonies,
+    population::{components::CitizenOf, events::CitizenDied},
+    snapshot::restore_snapshot,
+    SimulationSet, WorldColony,
+};
+
+pub struct StabilityPlugin;
+
+impl Plugin for StabilityPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(
+                OnEnter(SimulationState::Running),
+                init_stability.after(init_colonies).after(restore_snapshot)
+            )
+            .add_systems(DailyTick, update_stability.in_set(SimulationSet::Statistics));
+    }
+}
+
+/// Colonies restored from a snapshot keep the window they were saved with,
+/// every other colony starts with an empty one.
+fn init_stability(
+    mut commands: Commands,
+    config: Res<ThirdLifeConfig>,
+    colonies: Query<(Entity, Option<&StabilityWindow>), With<WorldColony>>,
+) {
+    let config = config.stability();
+    for (colony, window) in &colonies {
+        match window {
+            Some(window) => commands.entity(colony).insert(classify(window, &config)),
+            None => commands.entity(colony).insert((Stability::default(), StabilityWindow::default())),
+        };
+    }
+}
+
+fn update_stability(
+    config: Res<ThirdLifeConfig>,
+    mut deaths: EventReader<CitizenDied>,
+    mut colonies: Query<(Entity, &mut Stability, &mut StabilityWindow)>,
+    citizens: Query<&CitizenOf>,
+    food: Query<(&FoodResource, &ResourceOf)>,
+) {
+    let config = config.stability();
+    let mut samples = colonies.iter()
+        .map(|(colony, ..)| (colony, StabilitySample { population: 0, food: 0., deaths: 0 }))
+        .collect::<BTreeMap<_, _>>();
+    for of in &citizens {
+        if let Some(sample) = samples.get_mut(&of.colony) {
+            sample.population += 1;
+        }
+    }
+    for died in deaths.read() {
+        if let Some(sample) = samples.get_mut(&died.colony) {
+            sample.deaths += 1;
+        }
+    }
+    for (food, of) in &food {
+        if let Some(sample) = samples.get_mut(&of.colony) {
+            sample.food += food.amount;
+        }
+    }
+
+    for (colony, mut stability, mut window) in &mut colonies {
+        window.days.push_back(samples[&colony]);
+        while window.days.len() > config.window_days().max(1) {
+            window.days.pop_front();
+        }
+        *stability = classify(&window, &config);
+    }
+}
+
+/// Least squares line through the values, as the value at the first day and
+/// the change per day.
+fn trend(values: &[f64]) -> (f64, f64) {
+    let n = values.len() as f64;
+    let mean_x = (n - 1.) / 2.;
+    let mean_y = values.iter().sum::<f64>() / n;
+    let (covariance, variance) = values.iter().enumerate()
+        .map(|(x, y)| (x as f64 - mean_x, y - mean_y))
+        .fold((0., 0.), |(c, v), (dx, dy)| (c + dx * dy, v + dx * dx));
+    let slope = if variance > 0. { covariance / variance } else { 0. };
+    (mean_y - slope * mean_x, slope)
+}
+
+/// Days until a value following the trend falls to `floor`, counted from the
+/// last day of the window.
+fn days_until(current: f64, slope: f64, floor: f64) -> Option<u32> {
+    (slope < 0.).then(|| ((current - floor).max(0.) / -slope).ceil() as u32)
+}
+
+/// Amount of times the values cross the trend line, ignoring swings within
+/// a band of `band` around it.
+fn trend_crossings(values: &[f64], start: f64, slope: f64, band: f64) -> usize {
+    let mut crossings = 0;
+    let mut side = 0.;
+    for (day, value) in values.iter().enumerate() {
+        let residual = value - (start + slope * day as f64);
+        if residual.abs() <= band {
+            continue;
+        }
+        let current = residual.signum();
+        if side != 0. && current != side {
+            crossings += 1;
+        }
+        side = current;
+    }
+    crossings
+}
+
+fn classify(window: &StabilityWindow, config: &StabilityConfig) -> Stability {
+    let population = window.days.iter().map(|d| d.population as f64).collect::<Vec<_>>();
+    let food = window.days.iter().map(|d| d.food as f64).collect::<Vec<_>>();
+    let current = population.last().copied().unwrap_or(0.);
+    let mean = population.iter().sum::<f64>() / population.len() as f64;
+    let (start, slope) = trend(&population);
+    let (_, food_slope) = trend(&food);
+
+    let growth = if mean > 0. { slope * 365. / mean } else { 0. };
+    let deaths = window.days.iter().map(|d| d.deaths).sum::<usize>() as f64;
+    let death_rate = if mean > 0. { deaths / mean * 365. / population.len() as f64 } else { 0. };
+    let collapse_population = config.collapse_population() as f64;
+
+    let residuals = population.iter().enumerate()
+        .map(|(day, value)| value - (start + slope * day as f64));
+    let (low, high) = residuals.fold((0f64, 0f64), |(low, high), r| (low.min(r), high.max(r)));
+    let amplitude = if mean > 0. { (high - low) / mean } else { 0. };
+    let oscillating = amplitude > config.oscillation_amplitude()
+        && trend_crossings(&population, start, slope, (high - low) / 4.) >= 4;
+
+    let tracked = window.days.len() >= config.min_days();
+    let label = if current < collapse_population {
+        StabilityLabel::Collapsed
+    } else if !tracked {
+        StabilityLabel::Stable
+    } else if growth < -config.stable_growth() {
+        StabilityLabel::Declining
+    } else if oscillating {
+        StabilityLabel::Oscillating
+    } else if growth > config.stable_growth() {
+        StabilityLabel::Growing
+    } else {
+        StabilityLabel::Stable
+    };
+
+    Stability {
+        label,
+        growth,
+        death_rate,
+        days_until_starvation: match food.last().copied().unwrap_or(0.) {
+            stock if stock <= 0. => Some(0),
+            stock => days_until(stock, food_slope, 0.).filter(|_| tracked),
+        },
+        days_to_collapse: match label {
+            StabilityLabel::Collapsed => Some(0),
+            _ => days_until(current, slope, collapse_population).filter(|_| tracked),
+        },
+    }
+}
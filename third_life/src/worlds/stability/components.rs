@@ -0,0 +1,66 @@
+use std::{collections::VecDeque, fmt};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// How a world has been doing over the last days, updated once per game
+/// day.
+#[derive(Component, Debug, Clone, Copy, Default, Serialize)]
+pub struct Stability {
+    pub label: StabilityLabel,
+    /// Relative change of the population per year, following the trend of
+    /// the window.
+    pub growth: f64,
+    /// Deaths per citizen per year within the window.
+    pub death_rate: f64,
+    /// Game days until the food stocks run out if they keep following their
+    /// trend, `None` if they don't go down. `0` once they are empty.
+    pub days_until_starvation: Option<u32>,
+    /// Game days until the population falls below the collapse threshold if
+    /// it keeps following its trend, `None` if it doesn't go down.
+    pub days_to_collapse: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StabilityLabel {
+    Growing,
+    #[default]
+    Stable,
+    Oscillating,
+    Declining,
+    Collapsed,
+}
+
+impl StabilityLabel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StabilityLabel::Growing => "growing",
+            StabilityLabel::Stable => "stable",
+            StabilityLabel::Oscillating => "oscillating",
+            StabilityLabel::Declining => "declining",
+            StabilityLabel::Collapsed => "collapsed",
+        }
+    }
+}
+
+impl fmt::Display for StabilityLabel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The values of the last game days a [`Stability`] is based on, oldest
+/// first.
+#[derive(Component, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StabilityWindow {
+    pub days: VecDeque<StabilitySample>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct StabilitySample {
+    pub population: usize,
+    /// Processed food, which is what the citizens eat.
+    pub food: f32,
+    pub deaths: usize,
+}
@@ -14,7 +14,7 @@ use chrono::NaiveDate;
 use egui_plot::{Plot, BarChart, Legend, Bar, PlotPoint, PlotPoints, Line};
 use crate::{config::ThirdLifeConfig, time::GameDate, SimulationState};
 
-use super::{init_colonies, WorldEntity};
+use super::{calendar::LocalCalendar, init_colonies, stability::components::{Stability, StabilityLabel}, WorldEntity};
 
 
 pub struct WorldsUiPlugin;
@@ -47,12 +47,14 @@ fn display_world_uis(
     game_date: Res<GameDate>,
     ui_data: Query<(
         &WorldUiName,
+        &WorldUiEntity,
         &ResourceStorage,
         &PopulationHistorgram,
         &PopulationDeathLines,
     )>,
+    colonies: Query<(&Stability, &LocalCalendar)>,
 ) {
-    for (world, stor, pop, death) in &ui_data {
+    for (world, colony, stor, pop, death) in &ui_data {
         let name = &world.0;
         Window::new(format!("Window of {name}"))
             .default_open(false)
@@ -60,6 +62,12 @@ fn display_world_uis(
                 let start_date = NaiveDate::from_ymd_opt(config.starting_day().year(),config.starting_day().month(), config.starting_day().day()).unwrap();
                 ui.label(format!("Date: {}", game_date.date));
                 ui.label(format!("Years Elapsed: {}", game_date.date.years_since(start_date).unwrap()));
+                if let Ok((stability, calendar)) = colonies.get(colony.0) {
+                    ui.label(format!(
+                        "Local day {} of year {}", calendar.day_of_year() + 1, calendar.year() + 1
+                    ));
+                    stability_label(ui, stability);
+                }
                 ui.separator();
                 resources_storage(name, ui, &stor);
                 ui.separator();
@@ -74,6 +82,25 @@ fn display_world_uis(
 
 
 
+fn stability_label(ui: &mut Ui, stability: &Stability) {
+    let color = match stability.label {
+        StabilityLabel::Growing | StabilityLabel::Stable => Color32::GREEN,
+        StabilityLabel::Oscillating => Color32::YELLOW,
+        StabilityLabel::Declining | StabilityLabel::Collapsed => Color32::RED,
+    };
+    ui.horizontal(|ui| {
+        ui.label("Stability:");
+        ui.colored_label(color, stability.label.as_str());
+        ui.label(format!("({:+.1}% per year)", stability.growth * 100.));
+        if let Some(days) = stability.days_to_collapse.filter(|d| *d > 0) {
+            ui.label(format!("collapse in about {days} days"));
+        }
+        if let Some(days) = stability.days_until_starvation {
+            ui.label(format!("food runs out in about {days} days"));
+        }
+    });
+}
+
 pub fn f32_to_plotpoints(
     vec: &Vec<f32>
 ) -> PlotPoints {
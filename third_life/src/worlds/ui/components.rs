@@ -27,6 +27,7 @@ pub struct PopulationDeathLines {
     pub old_age_deaths: Vec<usize>,
     pub starvation_deaths: Vec<usize>,
     pub infant_deaths: Vec<usize>,
+    pub plague_deaths: Vec<usize>,
 }
 
 impl PopulationDeathLines {
@@ -35,7 +36,8 @@ impl PopulationDeathLines {
             timer: Timer::from_seconds(0.5, TimerMode::Repeating),
             old_age_deaths: vec![0],
             starvation_deaths: vec![0],
-            infant_deaths: vec![0]
+            infant_deaths: vec![0],
+            plague_deaths: vec![0]
         }
     }
     pub fn new_step(&mut self, delta: Duration) {
@@ -43,6 +45,7 @@ impl PopulationDeathLines {
             self.old_age_deaths.push(0);
             self.starvation_deaths.push(0);
             self.infant_deaths.push(0);
+            self.plague_deaths.push(0);
         }
         if self.old_age_deaths.len() > 25 {
             self.old_age_deaths.remove(0);
@@ -53,6 +56,9 @@ impl PopulationDeathLines {
         if self.infant_deaths.len() > 25 {
             self.infant_deaths.remove(0);
         }
+        if self.plague_deaths.len() > 25 {
+            self.plague_deaths.remove(0);
+        }
     }
 }
 
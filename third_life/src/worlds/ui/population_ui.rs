@@ -1,5 +1,5 @@
 
-use crate::{worlds::{ui::components::*, population::{events::{CitizenCreated, CitizenDied, DeathReason}, components::{Citizen, CitizenOf, Population}}}, time::DateChanged, SimulationState};
+use crate::{worlds::{ui::components::*, population::{events::{CitizenCreated, CitizenDied, DeathReason}, components::{Citizen, CitizenOf, Population}, update_population}, SimulationSet}, time::DailyTick, SimulationState};
 use std::collections::HashMap;
 use bevy::{prelude::*, reflect::List};
 use bevy_egui::{EguiContexts, egui::{Color32, Ui}};
@@ -16,10 +16,12 @@ impl Plugin for PopulationUiPlugin {
         app
             .add_systems(Update, (
                     add_citizens_to_population_histogram,
-                    update_ages,
-                    update_general_pop,
                     death_events_listener
-            ).run_if(in_state(SimulationState::Running)));
+            ).run_if(in_state(SimulationState::Running)))
+            .add_systems(DailyTick, (
+                    update_ages,
+                    update_general_pop.after(update_population),
+            ).in_set(SimulationSet::Statistics));
     }
 }
 
@@ -147,6 +149,11 @@ pub fn death_lines(
                     .color(Color32::from_rgb(0, 255, 0))
                     .name("infant death")
             );
+            plot_ui.line(
+                Line::new(usize_to_plotpoints(&deaths.plague_deaths))
+                    .color(Color32::from_rgb(160, 0, 160))
+                    .name("plague")
+            );
         });
 }
 
@@ -156,23 +163,25 @@ pub fn death_events_listener(
     mut uis: Query<(&WorldUiEntity, &mut PopulationDeathLines)>
 ) {
     let mapped_events = events.read().into_iter()
-        .fold(HashMap::new(), |mut acc: HashMap<Entity, (usize, usize, usize)>, e| {
-            let col_map = acc.entry(e.colony).or_insert((0, 0, 0));
+        .fold(HashMap::new(), |mut acc: HashMap<Entity, (usize, usize, usize, usize)>, e| {
+            let col_map = acc.entry(e.colony).or_insert((0, 0, 0, 0));
             match e.reason {
                 DeathReason::OldAge => col_map.0 += 1,
                 DeathReason::Starvation => col_map.1 += 1,
                 DeathReason::InfantDeath => col_map.2 += 1,
+                DeathReason::Plague => col_map.3 += 1,
             };
             acc
         });
 
     for (WorldUiEntity(colony), mut lines) in uis.iter_mut() {
         lines.new_step(time.delta());
-        let (old_age, starvation, infant) = mapped_events.get(&colony)
-            .map(|(old_age, starvation, infant)|(*old_age, *starvation, *infant))
-            .unwrap_or((0, 0, 0));
+        let (old_age, starvation, infant, plague) = mapped_events.get(&colony)
+            .copied()
+            .unwrap_or((0, 0, 0, 0));
         *lines.old_age_deaths.last_mut().unwrap() += old_age;
         *lines.starvation_deaths.last_mut().unwrap() += starvation;
         *lines.infant_deaths.last_mut().unwrap() += infant;
+        *lines.plague_deaths.last_mut().unwrap() += plague;
     }
 }
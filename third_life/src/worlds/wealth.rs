@@ -4,25 +4,25 @@ use std::collections::HashMap;
 
 use bevy::prelude::*;
 
-use crate::SimulationState;
+use crate::time::DailyTick;
 
 use self::components::WealthAndSpending;
 
-use super::population::components::{CitizenOf, Employed, Population};
+use super::{population::components::{CitizenOf, Employed, Population}, SimulationSet};
 
 pub struct WealthPlugin;
 
 impl Plugin for WealthPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
-            Update,
-            (calculate_wealth).run_if(in_state(SimulationState::Running))
+            DailyTick,
+            (calculate_wealth).in_set(SimulationSet::Economy)
         );
     }
 }
 
 
-fn calculate_wealth(
+pub(crate) fn calculate_wealth(
     working_citizens: Query<&CitizenOf, Without<Employed>>,
     mut colonies: Query<(Entity, &mut WealthAndSpending, &Population)>,
 ) {
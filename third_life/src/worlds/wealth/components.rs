@@ -1,7 +1,8 @@
 
 use bevy::{prelude::*, utils::warn};
+use serde::{Deserialize, Serialize};
 
-use crate::worlds::config::GovernmentConfig;
+use crate::worlds::config::{GovernmentConfig, PolicySwitch};
 
 #[derive(Bundle)]
 pub struct ColonyWealthBundle {
@@ -26,7 +27,7 @@ impl ColonyWealthBundle {
     }
 }
 
-#[derive(Component, Default)]
+#[derive(Component, Default, Serialize, Deserialize)]
 pub struct WealthAndSpending {
     pub total_wealth: f32,
     /// 0 to 1 number that reppresents how many percent of total gdp are 
@@ -57,6 +58,23 @@ impl WealthAndSpending {
     pub fn total_environmental_spending(&self) -> f32 {
         Self::to_01(self.policy.environmental_spending) * self.spending_available
     }
+    /// Applies the parts of the policy that are set in the switch.
+    pub fn switch_policy(&mut self, switch: &PolicySwitch) {
+        if let Some(payout) = switch.citizen_payout {
+            self.citizen_payout = payout;
+        }
+        let policy = &mut self.policy;
+        for (current, new) in [
+            (&mut policy.civil_spending, switch.civil_spending),
+            (&mut policy.sanitation_spending, switch.sanitation_spending),
+            (&mut policy.social_spending, switch.social_spending),
+            (&mut policy.environmental_spending, switch.environmental_spending),
+        ] {
+            if let Some(new) = new {
+                *current = new;
+            }
+        }
+    }
     fn to_01(num: usize) -> f32 {
         num as f32 / 100.
     }
@@ -65,7 +83,7 @@ impl WealthAndSpending {
 /// Contains percentages of how the remaining gdp should be spent. The
 /// numbers schould all be between 0 and 100 and in total come together
 /// to be 100 when added up
-#[derive(Default)]
+#[derive(Default, Clone, Serialize, Deserialize)]
 pub struct SpendingPolicy {
     civil_spending: usize,
     sanitation_spending: usize,